@@ -0,0 +1,109 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::parser::{TimestampedUsage, TokenUsage};
+use crate::pricing::PricingTable;
+
+/// A usage block: Claude's limits reset in fixed-length blocks that start at
+/// the first message sent after the previous block expired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBlock {
+    pub start: DateTime<Utc>,
+    /// When the block expires and the limit resets
+    pub end: DateTime<Utc>,
+    /// Timestamp of the last message in the block
+    pub last_activity: DateTime<Utc>,
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    /// Usage split by model id
+    pub models: BTreeMap<String, TokenUsage>,
+    /// Number of API requests in the block
+    pub request_count: u32,
+    /// The block hasn't expired yet
+    pub is_active: bool,
+}
+
+impl SessionBlock {
+    fn new(start: DateTime<Utc>, block_hours: i64, now: DateTime<Utc>) -> Self {
+        let end = start + Duration::hours(block_hours);
+        Self {
+            start,
+            end,
+            last_activity: start,
+            usage: TokenUsage::default(),
+            cost_usd: 0.0,
+            models: BTreeMap::new(),
+            request_count: 0,
+            is_active: end > now,
+        }
+    }
+}
+
+/// Split usages into blocks of `block_hours`, oldest first.
+/// Only the last block can be active.
+pub fn compute_blocks<'a>(
+    usages: impl IntoIterator<Item = &'a TimestampedUsage>,
+    block_hours: i64,
+    pricing: &PricingTable,
+    now: DateTime<Utc>,
+) -> Vec<SessionBlock> {
+    recent_blocks(usages, block_hours, pricing, now, usize::MAX)
+}
+
+/// The last `count` blocks of [`compute_blocks`], oldest first. Older usages
+/// only decide where blocks start; they aren't priced or summed.
+pub fn recent_blocks<'a>(
+    usages: impl IntoIterator<Item = &'a TimestampedUsage>,
+    block_hours: i64,
+    pricing: &PricingTable,
+    now: DateTime<Utc>,
+    count: usize,
+) -> Vec<SessionBlock> {
+    let mut usages: Vec<&TimestampedUsage> = usages.into_iter().collect();
+    usages.sort_by_key(|tu| tu.timestamp);
+
+    // Where each block starts depends on every earlier block, but only on timestamps
+    let length = Duration::hours(block_hours);
+    let mut starts = Vec::new();
+    let mut end: Option<DateTime<Utc>> = None;
+    for (i, tu) in usages.iter().enumerate() {
+        if end.map(|end| tu.timestamp >= end).unwrap_or(true) {
+            starts.push(i);
+            end = Some(tu.timestamp + length);
+        }
+    }
+    let first = starts
+        .get(starts.len().saturating_sub(count))
+        .copied()
+        .unwrap_or(usages.len());
+
+    let mut blocks: Vec<SessionBlock> = Vec::new();
+    for tu in &usages[first..] {
+        let starts_new_block = blocks.last().map(|b| tu.timestamp >= b.end).unwrap_or(true);
+        if starts_new_block {
+            blocks.push(SessionBlock::new(tu.timestamp, block_hours, now));
+        }
+
+        let block = blocks.last_mut().expect("block pushed above");
+        block.last_activity = tu.timestamp;
+        block.usage += tu.usage.clone();
+        block.cost_usd += pricing.cost(&tu.model, &tu.usage);
+        *block.models.entry(tu.model.clone()).or_default() += tu.usage.clone();
+        block.request_count += 1;
+    }
+
+    blocks
+}
+
+/// The block that hasn't expired yet, if any
+pub fn current_block<'a>(
+    usages: impl IntoIterator<Item = &'a TimestampedUsage>,
+    block_hours: i64,
+    pricing: &PricingTable,
+    now: DateTime<Utc>,
+) -> Option<SessionBlock> {
+    recent_blocks(usages, block_hours, pricing, now, 1)
+        .pop()
+        .filter(|b| b.is_active)
+}
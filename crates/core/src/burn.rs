@@ -0,0 +1,89 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{BudgetInfo, TimestampedUsage};
+
+/// Billable tokens per minute over one recent interval
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnRate {
+    pub interval_minutes: i64,
+    pub tokens_per_minute: f64,
+}
+
+/// Burn rates and when the budget runs out at the current pace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BurnRateInfo {
+    /// One rate per configured interval, in configuration order
+    pub rates: Vec<BurnRate>,
+    /// Rate the projection is based on (the first interval's)
+    pub tokens_per_minute: f64,
+    /// When the remaining budget is used up at this rate, if before the reset
+    pub exhaustion_time: Option<DateTime<Utc>>,
+    pub minutes_to_exhaustion: Option<i64>,
+    /// The budget lasts until the current block resets at this rate
+    pub reaches_reset: bool,
+}
+
+impl BurnRateInfo {
+    /// Compute burn rates over the last `intervals` minutes and project the budget forward
+    pub fn new<'a>(
+        usages: impl IntoIterator<Item = &'a TimestampedUsage>,
+        intervals: &[i64],
+        budget: &BudgetInfo,
+        now: DateTime<Utc>,
+    ) -> Self {
+        // Intervals reaching back before the earliest representable time cover everything
+        let since = |minutes: i64| {
+            Duration::try_minutes(minutes)
+                .and_then(|d| now.checked_sub_signed(d))
+                .unwrap_or(DateTime::<Utc>::MIN_UTC)
+        };
+        let longest = intervals.iter().copied().max().unwrap_or(0);
+        let recent: Vec<&TimestampedUsage> = usages
+            .into_iter()
+            .filter(|tu| tu.timestamp > since(longest) && tu.timestamp <= now)
+            .collect();
+
+        let rates: Vec<BurnRate> = intervals
+            .iter()
+            .map(|&minutes| {
+                let since = since(minutes);
+                let tokens: u64 = recent
+                    .iter()
+                    .filter(|tu| tu.timestamp > since)
+                    .map(|tu| tu.usage.billable())
+                    .sum();
+                BurnRate {
+                    interval_minutes: minutes,
+                    tokens_per_minute: tokens as f64 / minutes as f64,
+                }
+            })
+            .collect();
+
+        let tokens_per_minute = rates.first().map(|r| r.tokens_per_minute).unwrap_or(0.0);
+
+        // Time until the remaining budget is gone at the current pace; a budget
+        // lasting beyond the representable durations and dates never runs out
+        let until_empty = if budget.remaining == 0 {
+            Some(Duration::zero())
+        } else if tokens_per_minute > 0.0 {
+            // Casting saturates, so huge results are rejected by try_seconds
+            let secs = budget.remaining as f64 / tokens_per_minute * 60.0;
+            Duration::try_seconds(secs as i64)
+        } else {
+            None
+        };
+
+        let exhaustion_time = until_empty
+            .and_then(|d| now.checked_add_signed(d))
+            .filter(|t| budget.reset_time.map(|reset| *t < reset).unwrap_or(true));
+
+        Self {
+            rates,
+            tokens_per_minute,
+            minutes_to_exhaustion: exhaustion_time.map(|t| (t - now).num_minutes()),
+            reaches_reset: exhaustion_time.is_none(),
+            exhaustion_time,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::parser::DEFAULT_ROOT_LABEL;
+
+/// Environment variable listing data roots as comma-separated `[LABEL=]PATH` entries
+pub const ROOTS_VAR: &str = "CLAUDE_MONITOR_ROOTS";
+
+/// Environment variable selecting another config file
+pub const CONFIG_VAR: &str = "CLAUDE_MONITOR_CONFIG";
+
+/// Claude Code's own variable for its data directory; may list several
+pub const CLAUDE_CONFIG_DIR_VAR: &str = "CLAUDE_CONFIG_DIR";
+
+/// A Claude Code data directory (`~/.claude` or a `CLAUDE_CONFIG_DIR`)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DataRoot {
+    /// Short name shown in stats (e.g. "work", "personal")
+    pub label: String,
+    pub path: PathBuf,
+}
+
+impl DataRoot {
+    /// Path to projects directory
+    pub fn projects_dir(&self) -> PathBuf {
+        self.path.join("projects")
+    }
+
+    /// Path to history file
+    pub fn history_file(&self) -> PathBuf {
+        self.path.join("history.jsonl")
+    }
+
+    /// Label derived from the directory name (`~/.claude-work` becomes "claude-work")
+    fn label_for(path: &Path) -> String {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.trim_start_matches('.').to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| DEFAULT_ROOT_LABEL.to_string())
+    }
+}
+
+impl FromStr for DataRoot {
+    type Err = String;
+
+    /// Parse `LABEL=PATH` or a bare `PATH`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("empty data root".to_string());
+        }
+
+        let (label, path) = match s.split_once('=') {
+            Some((label, path)) => (
+                Some(label.trim().to_string()),
+                expand_tilde(Path::new(path.trim())),
+            ),
+            None => (None, expand_tilde(Path::new(s))),
+        };
+        let label = label.unwrap_or_else(|| Self::label_for(&path));
+
+        Ok(Self { label, path })
+    }
+}
+
+/// Where a config value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File,
+    Env(String),
+    Cli,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File => write!(f, "config file"),
+            Source::Env(var) => write!(f, "env {}", var),
+            Source::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Directory of Claude Monitor's config files: `$XDG_CONFIG_HOME/claude-monitor`
+/// if that is absolute, otherwise `~/.config/claude-monitor` on every platform
+/// (rather than Application Support on macOS)
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .map(|dir| dir.join("claude-monitor"))
+}
+
+/// Config file named by `CLAUDE_MONITOR_CONFIG`, or `config.toml` in [`config_dir`]
+pub fn config_file() -> Option<PathBuf> {
+    match std::env::var_os(CONFIG_VAR) {
+        Some(path) if !path.is_empty() => Some(expand_tilde(Path::new(&path))),
+        _ => config_dir().map(|dir| dir.join("config.toml")),
+    }
+}
+
+/// The data root Claude Code uses by default, `~/.claude`
+pub fn default_roots() -> Option<Vec<DataRoot>> {
+    dirs::home_dir().map(|home| {
+        vec![DataRoot {
+            label: DEFAULT_ROOT_LABEL.to_string(),
+            path: home.join(".claude"),
+        }]
+    })
+}
+
+/// Resolve data roots: default < `CLAUDE_CONFIG_DIR` < file < `CLAUDE_MONITOR_ROOTS` < cli.
+/// `CLAUDE_MONITOR_ROOTS` takes `[LABEL=]PATH` entries. Claude Code's own
+/// `CLAUDE_CONFIG_DIR` (comma-separated paths) only replaces the default, since
+/// it is often exported for Claude Code rather than for the monitor.
+pub fn resolve_roots(
+    default: Vec<DataRoot>,
+    file: Option<Vec<DataRoot>>,
+    cli: &[DataRoot],
+) -> Result<(Vec<DataRoot>, Source), Box<dyn std::error::Error>> {
+    let env_roots = |var: &str| -> Result<Option<Vec<DataRoot>>, Box<dyn std::error::Error>> {
+        match std::env::var(var) {
+            Ok(value) if !value.trim().is_empty() => value
+                .split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| {
+                    s.parse()
+                        .map_err(|e| format!("Invalid value for {}: {}", var, e).into())
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Some),
+            _ => Ok(None),
+        }
+    };
+
+    let (roots, source) = if !cli.is_empty() {
+        (cli.to_vec(), Source::Cli)
+    } else if let Some(roots) = env_roots(ROOTS_VAR)? {
+        (roots, Source::Env(ROOTS_VAR.to_string()))
+    } else if let Some(roots) = file {
+        let roots = roots
+            .into_iter()
+            .map(|r| DataRoot {
+                path: expand_tilde(&r.path),
+                ..r
+            })
+            .collect();
+        (roots, Source::File)
+    } else if let Some(roots) = env_roots(CLAUDE_CONFIG_DIR_VAR)? {
+        (roots, Source::Env(CLAUDE_CONFIG_DIR_VAR.to_string()))
+    } else {
+        (default, Source::Default)
+    };
+
+    if roots.is_empty() {
+        return Err("At least one data root is required".into());
+    }
+    for (i, root) in roots.iter().enumerate() {
+        if roots[..i].iter().any(|r| r.label == root.label) {
+            return Err(format!("Duplicate data root label \"{}\"", root.label).into());
+        }
+    }
+
+    Ok((roots, source))
+}
+
+/// The `[[roots]]` of a config file; other settings are the server's business
+#[derive(Debug, Default, Deserialize)]
+struct RootsFile {
+    roots: Option<Vec<DataRoot>>,
+}
+
+/// Data roots as the server would resolve them without command line flags,
+/// from [`config_file`] and the environment
+pub fn load_roots() -> Result<Vec<DataRoot>, Box<dyn std::error::Error>> {
+    let default = default_roots().ok_or("Could not find home directory")?;
+    let file = match config_file() {
+        Some(path) if path.exists() => {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+            toml::from_str::<RootsFile>(&content)
+                .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?
+        }
+        _ => RootsFile::default(),
+    };
+
+    resolve_roots(default, file.roots, &[]).map(|(roots, _)| roots)
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,267 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::parser::session::SessionMessage;
+use crate::parser::{SessionData, TimestampedUsage, TokenUsage};
+use crate::pricing::PricingTable;
+use crate::timeseries::Timeseries;
+use crate::tools::{self, ToolSummary};
+
+/// One message of a session with running totals
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageDetail {
+    pub timestamp: Option<DateTime<Utc>>,
+    /// "user" or "assistant"
+    pub role: String,
+    pub model: Option<String>,
+    pub usage: Option<TokenUsage>,
+    /// Tokens used by the session up to and including this message
+    pub cumulative: TokenUsage,
+    pub cost_usd: f64,
+}
+
+/// A single session with its per-message timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDetail {
+    pub session: SessionData,
+    /// Messages in transcript order
+    pub messages: Vec<MessageDetail>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_secs: Option<i64>,
+    /// Share of input tokens served from the prompt cache
+    pub cache_hit_ratio: f64,
+    /// Tool calls, most called first
+    pub tools: Vec<ToolSummary>,
+    /// The transcript is gone; the timeline only has indexed usage
+    pub from_index: bool,
+}
+
+impl SessionDetail {
+    /// Build the timeline from a re-parsed transcript
+    pub fn new(
+        session: SessionData,
+        messages: Vec<SessionMessage>,
+        pricing: &PricingTable,
+    ) -> Self {
+        let mut cumulative = TokenUsage::default();
+        let messages = messages
+            .into_iter()
+            .map(|m| {
+                let cost_usd = match (&m.model, &m.usage) {
+                    (Some(model), Some(usage)) => pricing.cost(model, usage),
+                    _ => 0.0,
+                };
+                if let Some(usage) = &m.usage {
+                    cumulative += usage.clone();
+                }
+                MessageDetail {
+                    timestamp: m.timestamp,
+                    role: m.role,
+                    model: m.model,
+                    usage: m.usage,
+                    cumulative: cumulative.clone(),
+                    cost_usd,
+                }
+            })
+            .collect();
+
+        Self::summarize(session, messages, false)
+    }
+
+    /// Build the timeline from indexed usage when the transcript no longer exists
+    pub fn from_usages(
+        session: SessionData,
+        usages: &[TimestampedUsage],
+        pricing: &PricingTable,
+    ) -> Self {
+        let mut usages: Vec<&TimestampedUsage> = usages.iter().collect();
+        usages.sort_by_key(|tu| tu.timestamp);
+
+        let mut cumulative = TokenUsage::default();
+        let messages = usages
+            .into_iter()
+            .map(|tu| {
+                cumulative += tu.usage.clone();
+                MessageDetail {
+                    timestamp: Some(tu.timestamp),
+                    role: "assistant".to_string(),
+                    model: Some(tu.model.clone()),
+                    usage: Some(tu.usage.clone()),
+                    cumulative: cumulative.clone(),
+                    cost_usd: pricing.cost(&tu.model, &tu.usage),
+                }
+            })
+            .collect();
+
+        Self::summarize(session, messages, true)
+    }
+
+    fn summarize(session: SessionData, messages: Vec<MessageDetail>, from_index: bool) -> Self {
+        let started_at = messages.iter().filter_map(|m| m.timestamp).min();
+        let ended_at = messages.iter().filter_map(|m| m.timestamp).max();
+        let total = messages
+            .last()
+            .map(|m| m.cumulative.clone())
+            .unwrap_or_default();
+        let cache_hit_ratio = if total.total_input() > 0 {
+            total.cache_read_input_tokens as f64 / total.total_input() as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            messages,
+            started_at,
+            ended_at,
+            duration_secs: started_at
+                .zip(ended_at)
+                .map(|(start, end)| (end - start).num_seconds()),
+            cache_hit_ratio,
+            tools: tools::summarize([&session]),
+            session,
+            from_index,
+        }
+    }
+}
+
+/// Totals of one kind of session (main sessions or agents) within a project
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionTypeStats {
+    pub session_count: u32,
+    pub message_count: u32,
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+}
+
+impl SessionTypeStats {
+    fn add(&mut self, session: &SessionData) {
+        self.session_count += 1;
+        self.message_count += session.message_count;
+        self.usage += session.usage.clone();
+        self.cost_usd += session.cost_usd;
+    }
+}
+
+/// Usage attributed to one git branch of a project
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchStats {
+    /// Branch name; `None` for usage outside a git repository or in older transcripts
+    pub branch: Option<String>,
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    /// Sessions with usage on the branch
+    pub session_count: u32,
+    pub request_count: u32,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Aggregate `(session key, usage)` pairs per git branch, most tokens first
+pub fn branch_stats<'a>(
+    usages: impl IntoIterator<Item = (&'a str, &'a TimestampedUsage)>,
+    pricing: &PricingTable,
+) -> Vec<BranchStats> {
+    let mut branches: HashMap<Option<&str>, (BranchStats, HashSet<&str>)> = HashMap::new();
+    for (key, tu) in usages {
+        let (stats, sessions) = branches
+            .entry(tu.git_branch.as_deref())
+            .or_insert_with(|| {
+                let stats = BranchStats {
+                    branch: tu.git_branch.clone(),
+                    usage: TokenUsage::default(),
+                    cost_usd: 0.0,
+                    session_count: 0,
+                    request_count: 0,
+                    first_seen: tu.timestamp,
+                    last_seen: tu.timestamp,
+                };
+                (stats, HashSet::new())
+            });
+        stats.usage += tu.usage.clone();
+        stats.cost_usd += pricing.cost(&tu.model, &tu.usage);
+        stats.request_count += 1;
+        stats.first_seen = stats.first_seen.min(tu.timestamp);
+        stats.last_seen = stats.last_seen.max(tu.timestamp);
+        sessions.insert(key);
+    }
+
+    let mut branches: Vec<BranchStats> = branches
+        .into_values()
+        .map(|(mut stats, sessions)| {
+            stats.session_count = sessions.len() as u32;
+            stats
+        })
+        .collect();
+    branches.sort_by_key(|b| std::cmp::Reverse(b.usage.total()));
+    branches
+}
+
+/// A project with its full session history
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectDetail {
+    pub path: String,
+    pub usage: TokenUsage,
+    /// Usage split by model id
+    pub models: BTreeMap<String, TokenUsage>,
+    pub cost_usd: f64,
+    pub main: SessionTypeStats,
+    pub agents: SessionTypeStats,
+    /// Total tokens divided by the number of sessions, agents included
+    pub avg_tokens_per_session: f64,
+    /// Usage per git branch, most tokens first
+    pub branches: Vec<BranchStats>,
+    /// Tool calls across the project's sessions, most called first
+    pub tools: Vec<ToolSummary>,
+    /// Every session, most recently active first
+    pub sessions: Vec<SessionData>,
+    /// Daily usage over the last days, grouped into "main" and "agent"
+    pub daily: Timeseries,
+}
+
+impl ProjectDetail {
+    pub fn new(
+        path: String,
+        mut sessions: Vec<SessionData>,
+        branches: Vec<BranchStats>,
+        daily: Timeseries,
+    ) -> Self {
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
+
+        let mut main = SessionTypeStats::default();
+        let mut agents = SessionTypeStats::default();
+        let mut models: BTreeMap<String, TokenUsage> = BTreeMap::new();
+        for session in &sessions {
+            if session.is_agent {
+                agents.add(session);
+            } else {
+                main.add(session);
+            }
+            for (model, usage) in &session.models {
+                *models.entry(model.clone()).or_default() += usage.clone();
+            }
+        }
+
+        let usage = main.usage.clone() + agents.usage.clone();
+        let avg_tokens_per_session = if sessions.is_empty() {
+            0.0
+        } else {
+            usage.total() as f64 / sessions.len() as f64
+        };
+
+        Self {
+            path,
+            cost_usd: main.cost_usd + agents.cost_usd,
+            usage,
+            models,
+            main,
+            agents,
+            avg_tokens_per_session,
+            branches,
+            tools: tools::summarize(&sessions),
+            sessions,
+            daily,
+        }
+    }
+}
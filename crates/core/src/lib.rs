@@ -0,0 +1,17 @@
+//! Parsing of Claude Code transcripts and aggregation of their token usage,
+//! shared by the Claude Monitor server and tray app.
+
+pub mod blocks;
+pub mod burn;
+pub mod config;
+pub mod detail;
+pub mod parser;
+pub mod pricing;
+pub mod server;
+pub mod stats;
+pub mod timeseries;
+pub mod tools;
+
+pub use parser::{SessionData, TimestampedUsage, TokenUsage};
+pub use pricing::PricingTable;
+pub use stats::{compute_stats, Stats, StatsSettings};
@@ -17,7 +17,7 @@ pub struct HistoryEntry {
 impl HistoryEntry {
     pub fn timestamp_utc(&self) -> Option<DateTime<Utc>> {
         self.timestamp.map(|ts| {
-            DateTime::from_timestamp_millis(ts).unwrap_or_else(|| Utc::now())
+            DateTime::from_timestamp_millis(ts).unwrap_or_else(Utc::now)
         })
     }
 }
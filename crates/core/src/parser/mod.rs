@@ -0,0 +1,8 @@
+pub mod history;
+pub mod project;
+pub mod session;
+
+pub use session::{
+    model_family, BudgetInfo, SessionData, TimestampedUsage, TokenUsage, DEFAULT_ROOT_LABEL,
+    DEFAULT_TOKEN_LIMIT, ROLLING_WINDOW_HOURS,
+};
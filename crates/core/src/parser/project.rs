@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Encode a path the way Claude Code names project directories:
+/// every character other than an ASCII letter or digit becomes '-'
+pub fn encode_project_dir(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Read every '-' of an encoded project directory name as '/'. Exact for
+/// paths without other punctuation and the fallback of [`decode_project_dir`].
+pub fn naive_project_dir(name: &str) -> String {
+    name.replace('-', "/")
+}
+
+/// Recover a project path from its encoded directory name by finding a
+/// directory below `search_root` (`/` for this machine's projects) that encodes
+/// to it. Falls back to [`naive_project_dir`] when no such directory exists
+/// (e.g. the project was moved or deleted). Results are cached, so the disk is
+/// only searched the first time a name is seen.
+pub fn decode_project_dir(name: &str, search_root: &Path) -> String {
+    static CACHE: OnceLock<Mutex<HashMap<(PathBuf, String), String>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    let key = (search_root.to_path_buf(), name.to_string());
+
+    if let Some(path) = cache.lock().unwrap().get(&key) {
+        return path.clone();
+    }
+
+    let path = name
+        .strip_prefix('-')
+        .and_then(|rest| find_dir(search_root, rest))
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| naive_project_dir(name));
+    cache.lock().unwrap().insert(key, path.clone());
+    path
+}
+
+/// Depth-first search below `dir` for a directory whose path relative to `dir`
+/// encodes to `rest`, preferring longer names at each level
+fn find_dir(dir: &Path, rest: &str) -> Option<PathBuf> {
+    let mut children: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| {
+            let encoded = encode_project_dir(&entry.file_name().to_string_lossy());
+            (encoded, entry.path())
+        })
+        .filter(|(encoded, _)| {
+            rest == encoded
+                || rest
+                    .strip_prefix(encoded.as_str())
+                    .map(|tail| tail.starts_with('-'))
+                    .unwrap_or(false)
+        })
+        .collect();
+    children.sort_by_key(|(encoded, _)| std::cmp::Reverse(encoded.len()));
+
+    children.into_iter().find_map(|(encoded, path)| {
+        if rest == encoded {
+            Some(path)
+        } else {
+            find_dir(&path, &rest[encoded.len() + 1..])
+        }
+    })
+}
@@ -0,0 +1,648 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::project::{decode_project_dir, encode_project_dir, naive_project_dir};
+
+/// Default rolling window duration in hours (Max plan = 5 hours)
+pub const ROLLING_WINDOW_HOURS: i64 = 5;
+
+/// Default token limit for Max plan (approximately 45M tokens per 5-hour window)
+pub const DEFAULT_TOKEN_LIMIT: u64 = 45_000_000;
+
+/// Label of the data root used when none is configured
+pub const DEFAULT_ROOT_LABEL: &str = "default";
+
+/// Token usage data from a Claude Code message
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn total_input(&self) -> u64 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total_input() + self.output_tokens
+    }
+
+    /// Billable tokens (cache reads are usually free or discounted)
+    pub fn billable(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_input_tokens
+    }
+}
+
+impl std::ops::Add for TokenUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens
+                + other.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens + other.cache_read_input_tokens,
+        }
+    }
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        *self = self.clone() + other;
+    }
+}
+
+/// Token usage with timestamp for rolling window calculation
+#[derive(Debug, Clone)]
+pub struct TimestampedUsage {
+    pub timestamp: DateTime<Utc>,
+    pub usage: TokenUsage,
+    /// Model that produced this usage (e.g. "claude-sonnet-4-5-20250929")
+    pub model: String,
+    /// Identifies the message within its session file: the message/request id
+    /// when present, otherwise the byte offset of its line
+    pub message_key: String,
+    /// Git branch checked out in the session's working directory
+    pub git_branch: Option<String>,
+}
+
+/// Placeholder model name for usage records that don't report a model
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// Map a model id to its family name (Opus, Sonnet, Haiku)
+pub fn model_family(model: &str) -> &'static str {
+    let lower = model.to_ascii_lowercase();
+    if lower.contains("opus") {
+        "Opus"
+    } else if lower.contains("sonnet") {
+        "Sonnet"
+    } else if lower.contains("haiku") {
+        "Haiku"
+    } else {
+        "Other"
+    }
+}
+
+/// A message entry in a session
+#[derive(Debug, Clone, Deserialize)]
+pub struct MessageEntry {
+    #[serde(rename = "type")]
+    pub entry_type: Option<String>,
+    pub message: Option<Message>,
+    pub timestamp: Option<String>,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    /// Working directory of the session when the entry was written
+    pub cwd: Option<String>,
+    /// Git branch checked out in `cwd` (empty outside a repository)
+    #[serde(rename = "gitBranch")]
+    pub git_branch: Option<String>,
+}
+
+impl MessageEntry {
+    /// Key identifying the API request behind this entry. Claude Code writes one
+    /// line per content block, all repeating the same message id and usage.
+    pub fn dedup_key(&self) -> Option<String> {
+        let message_id = self.message.as_ref().and_then(|m| m.id.as_deref());
+        match (message_id, self.request_id.as_deref()) {
+            (Some(id), Some(req)) => Some(format!("{}:{}", id, req)),
+            (Some(id), None) => Some(id.to_string()),
+            (None, Some(req)) => Some(req.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub id: Option<String>,
+    pub usage: Option<TokenUsage>,
+    pub model: Option<String>,
+    pub content: Option<MessageContent>,
+}
+
+/// Message content: a list of content blocks, or plain text
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Blocks(Vec<ContentBlock>),
+    Other(serde::de::IgnoredAny),
+}
+
+impl MessageContent {
+    pub fn blocks(&self) -> &[ContentBlock] {
+        match self {
+            MessageContent::Blocks(blocks) => blocks,
+            _ => &[],
+        }
+    }
+}
+
+/// A content block; only the fields needed for tool analytics are read
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentBlock {
+    #[serde(rename = "type")]
+    pub block_type: Option<String>,
+    /// Id of a `tool_use` block
+    pub id: Option<String>,
+    /// Tool name of a `tool_use` block (e.g. "Bash", "mcp__github__create_issue")
+    pub name: Option<String>,
+    /// Tool call a `tool_result` block answers
+    pub tool_use_id: Option<String>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+/// Invocations of one tool within a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub calls: u32,
+    /// Calls whose result was reported as an error
+    pub errors: u32,
+    /// Usage of the turns (API requests) that called the tool; a turn calling
+    /// several tools counts towards each of them
+    pub usage: TokenUsage,
+    /// Turn usage split by model id, for pricing
+    pub models: BTreeMap<String, TokenUsage>,
+    /// Estimated API-equivalent cost of those turns (filled in from the pricing table)
+    pub cost_usd: f64,
+}
+
+/// Aggregated session data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionData {
+    pub session_id: String,
+    /// Label of the data root the transcript was found in
+    #[serde(default = "default_root")]
+    pub root: String,
+    /// Project directory, from the transcript's `cwd` or decoded from the directory name
+    pub project_path: String,
+    pub usage: TokenUsage,
+    /// Usage split by model id
+    pub models: BTreeMap<String, TokenUsage>,
+    pub message_count: u32,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub is_agent: bool,
+    /// Most recent working directory recorded in the transcript
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Most recent git branch recorded in the transcript
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    /// Estimated API-equivalent cost in USD (filled in from the pricing table)
+    pub cost_usd: f64,
+    /// Tool invocations by tool name
+    #[serde(default)]
+    pub tools: BTreeMap<String, ToolStats>,
+    /// Dedup keys of messages already counted
+    #[serde(skip)]
+    pub seen_messages: HashSet<String>,
+    /// Names of called tools whose result hasn't been seen yet, by tool_use id.
+    /// Stored with the session, so results appended after a restart still count.
+    #[serde(default)]
+    pub pending_tools: HashMap<String, String>,
+    /// (turn, tool) pairs whose usage was already attributed to the tool;
+    /// stored so blocks of a turn appended after a restart aren't counted again
+    #[serde(default)]
+    pub tool_turns: HashSet<(String, String)>,
+}
+
+/// Root label for sessions indexed before multiple roots were supported
+fn default_root() -> String {
+    DEFAULT_ROOT_LABEL.to_string()
+}
+
+/// Budget information for the current usage block
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetInfo {
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    pub percentage: f64,
+    pub window_hours: i64,
+    pub reset_minutes: Option<i64>,
+    /// Actual reset time (when the current block expires)
+    pub reset_time: Option<DateTime<Utc>>,
+}
+
+impl BudgetInfo {
+    pub fn new(
+        used: u64,
+        limit: u64,
+        reset_time: Option<DateTime<Utc>>,
+        window_hours: i64,
+    ) -> Self {
+        let remaining = limit.saturating_sub(used);
+        let percentage = if limit > 0 {
+            (used as f64 / limit as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        let reset_minutes = reset_time.map(|expiry| {
+            let now = Utc::now();
+            if expiry > now {
+                (expiry - now).num_minutes()
+            } else {
+                0
+            }
+        });
+
+        Self {
+            limit,
+            used,
+            remaining,
+            percentage,
+            window_hours,
+            reset_minutes,
+            reset_time,
+        }
+    }
+}
+
+/// Key identifying a session in the app state
+pub fn session_key(root: &str, project_path: &str, session_id: &str) -> String {
+    format!("{}:{}:{}", root, project_path, session_id)
+}
+
+impl SessionData {
+    /// Empty session for a transcript file in data root `root`, identified from its path
+    pub fn from_path(path: &Path, root: &str) -> Self {
+        let file_name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+
+        let is_agent = file_name.starts_with("agent-");
+
+        let session_id = if is_agent {
+            file_name.strip_prefix("agent-").unwrap_or(file_name)
+        } else {
+            file_name
+        }
+        .to_string();
+
+        // Corrected by the transcript's cwd, or by parse_session_file
+        let project_path = project_dir_name(path)
+            .map(naive_project_dir)
+            .unwrap_or_default();
+
+        Self {
+            session_id,
+            root: root.to_string(),
+            project_path,
+            usage: TokenUsage::default(),
+            models: BTreeMap::new(),
+            message_count: 0,
+            last_activity: None,
+            is_agent,
+            cwd: None,
+            git_branch: None,
+            cost_usd: 0.0,
+            tools: BTreeMap::new(),
+            seen_messages: HashSet::new(),
+            pending_tools: HashMap::new(),
+            tool_turns: HashSet::new(),
+        }
+    }
+
+    /// Key identifying this session in the app state
+    pub fn key(&self) -> String {
+        session_key(&self.root, &self.project_path, &self.session_id)
+    }
+
+    /// Fold one JSONL line starting at byte `offset` into the session,
+    /// returning its timestamped usage if any
+    fn apply_line(&mut self, line: &str, offset: u64) -> Option<TimestampedUsage> {
+        let entry: MessageEntry = serde_json::from_str(line).ok()?;
+
+        // Parse timestamp
+        let timestamp = entry.timestamp.as_ref().and_then(|ts| {
+            DateTime::parse_from_rfc3339(ts)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        });
+
+        if let Some(ts) = timestamp {
+            if self.last_activity.map(|lt| ts > lt).unwrap_or(true) {
+                self.last_activity = Some(ts);
+            }
+        }
+
+        // The project directory name is the encoded launch directory; a cwd that
+        // encodes to the same name is the exact project path
+        if let Some(cwd) = &entry.cwd {
+            if *cwd != self.project_path
+                && encode_project_dir(cwd) == encode_project_dir(&self.project_path)
+            {
+                self.project_path = cwd.clone();
+            }
+            self.cwd = Some(cwd.clone());
+        }
+
+        let git_branch = entry.git_branch.clone().filter(|b| !b.is_empty());
+        if git_branch.is_some() {
+            self.git_branch = git_branch.clone();
+        }
+
+        // Content blocks are spread over the lines of a message, so tools are
+        // recorded before repeated lines are skipped
+        let dedup_key = entry.dedup_key();
+        if let Some(msg) = &entry.message {
+            let turn = dedup_key.clone().unwrap_or_else(|| offset.to_string());
+            self.record_tools(msg, &turn);
+        }
+
+        // Further content blocks of an already counted message
+        if let Some(key) = &dedup_key {
+            if !self.seen_messages.insert(key.clone()) {
+                return None;
+            }
+        }
+
+        if entry.entry_type.as_deref() == Some("assistant")
+            || entry.entry_type.as_deref() == Some("user")
+        {
+            self.message_count += 1;
+        }
+
+        let msg = entry.message?;
+        let msg_usage = msg.usage?;
+        let model = msg.model.unwrap_or_else(|| UNKNOWN_MODEL.to_string());
+
+        self.usage += msg_usage.clone();
+        *self.models.entry(model.clone()).or_default() += msg_usage.clone();
+
+        // Timestamped usage feeds the rolling window calculation
+        timestamp.map(|ts| TimestampedUsage {
+            timestamp: ts,
+            usage: msg_usage,
+            model,
+            message_key: dedup_key.unwrap_or_else(|| offset.to_string()),
+            git_branch,
+        })
+    }
+
+    /// Count the tool calls and failed tool results in a message's content blocks,
+    /// attributing the usage of the calling turn to each tool once
+    fn record_tools(&mut self, msg: &Message, turn: &str) {
+        let Some(content) = &msg.content else {
+            return;
+        };
+
+        for block in content.blocks() {
+            match block.block_type.as_deref() {
+                Some("tool_use") => {
+                    let name = block.name.clone().unwrap_or_else(|| "unknown".to_string());
+                    let stats = self.tools.entry(name.clone()).or_default();
+                    stats.calls += 1;
+
+                    if let Some(usage) = &msg.usage {
+                        if self.tool_turns.insert((turn.to_string(), name.clone())) {
+                            let model = msg.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+                            stats.usage += usage.clone();
+                            *stats.models.entry(model.to_string()).or_default() += usage.clone();
+                        }
+                    }
+                    if let Some(id) = &block.id {
+                        self.pending_tools.insert(id.clone(), name);
+                    }
+                }
+                Some("tool_result") => {
+                    let name = block
+                        .tool_use_id
+                        .as_ref()
+                        .and_then(|id| self.pending_tools.remove(id));
+                    if let (Some(name), true) = (name, block.is_error) {
+                        self.tools.entry(name).or_default().errors += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Whether a path looks like a session transcript
+pub fn is_session_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+}
+
+/// Session transcripts in every project directory below `projects_dir`
+pub fn session_files(projects_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for project_entry in fs::read_dir(projects_dir)? {
+        let project_path = project_entry?.path();
+        if !project_path.is_dir() {
+            continue;
+        }
+
+        for session_entry in fs::read_dir(&project_path)? {
+            let session_path = session_entry?.path();
+            if is_session_file(&session_path) {
+                files.push(session_path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Parse a whole session JSONL file.
+/// Returns session data, timestamped usages and the offset parsing stopped at.
+pub fn parse_session_file(
+    path: &Path,
+    root: &str,
+) -> Result<(SessionData, Vec<TimestampedUsage>, u64), Box<dyn std::error::Error>> {
+    let mut session = SessionData::from_path(path, root);
+    let (timestamped_usages, offset) = parse_session_tail(path, &mut session, 0)?;
+
+    // Only search the disk for the project when no cwd in the transcript
+    // confirmed the path
+    if let Some(name) = project_dir_name(path) {
+        let naive = naive_project_dir(name);
+        if session.project_path == naive && session.cwd.as_deref() != Some(naive.as_str()) {
+            session.project_path = decode_project_dir(name, Path::new("/"));
+        }
+    }
+    Ok((session, timestamped_usages, offset))
+}
+
+/// Name of the (encoded) project directory holding a transcript
+fn project_dir_name(path: &Path) -> Option<&str> {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+}
+
+/// Parse lines appended to a session file since byte `offset`, folding them into `session`.
+/// Returns the new timestamped usages and the offset just past the last complete line,
+/// so a line that is still being written is picked up on the next call.
+pub fn parse_session_tail(
+    path: &Path,
+    session: &mut SessionData,
+    offset: u64,
+) -> Result<(Vec<TimestampedUsage>, u64), Box<dyn std::error::Error>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+
+    let mut offset = offset;
+    let mut timestamped_usages: Vec<TimestampedUsage> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        let complete = buf.ends_with(b"\n");
+        let line = String::from_utf8_lossy(&buf);
+
+        // A trailing line without newline is only consumed if it's already valid JSON
+        if !complete && serde_json::from_str::<serde_json::Value>(&line).is_err() {
+            break;
+        }
+        let line_offset = offset;
+        offset += read as u64;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(tu) = session.apply_line(&line, line_offset) {
+            timestamped_usages.push(tu);
+        }
+    }
+
+    Ok((timestamped_usages, offset))
+}
+
+/// One user or assistant message of a session transcript
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionMessage {
+    pub timestamp: Option<DateTime<Utc>>,
+    /// "user" or "assistant"
+    pub role: String,
+    pub model: Option<String>,
+    /// Token usage of the API request behind an assistant message
+    pub usage: Option<TokenUsage>,
+}
+
+/// Read the user and assistant messages of a session file in order,
+/// counting a message split over several content-block lines once
+pub fn parse_session_messages(
+    path: &Path,
+) -> Result<Vec<SessionMessage>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut messages = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let Ok(entry) = serde_json::from_str::<MessageEntry>(&line) else {
+            continue;
+        };
+
+        let role = match entry.entry_type.as_deref() {
+            Some(role @ ("user" | "assistant")) => role.to_string(),
+            _ => continue,
+        };
+        if let Some(key) = entry.dedup_key() {
+            if !seen.insert(key) {
+                continue;
+            }
+        }
+
+        let timestamp = entry.timestamp.as_ref().and_then(|ts| {
+            DateTime::parse_from_rfc3339(ts)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        });
+        let (model, usage) = entry
+            .message
+            .map(|m| (m.model, m.usage))
+            .unwrap_or_default();
+
+        messages.push(SessionMessage {
+            timestamp,
+            role,
+            model,
+            usage,
+        });
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn repeated_content_blocks_are_counted_once() {
+        let (session, usages, _) =
+            parse_session_file(&fixture("duplicated-usage.jsonl"), DEFAULT_ROOT_LABEL).unwrap();
+
+        // Three unique requests, written as 3 + 2 + 1 lines
+        assert_eq!(usages.len(), 3);
+        assert_eq!(session.usage.input_tokens, 3 + 5 + 7);
+        assert_eq!(session.usage.output_tokens, 120 + 45 + 300);
+        assert_eq!(session.usage.cache_creation_input_tokens, 2_000 + 150);
+        assert_eq!(session.usage.cache_read_input_tokens, 10_000 + 12_000 + 12_150);
+        assert_eq!(session.usage.billable(), 15 + 465 + 2_150);
+
+        // Two user prompts, one tool result and three assistant messages
+        assert_eq!(session.message_count, 6);
+    }
+
+    #[test]
+    fn dedup_spans_incremental_parses() {
+        let content = std::fs::read_to_string(fixture("duplicated-usage.jsonl")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        // Split right after the first content block of the first request
+        let mut session =
+            SessionData::from_path(&fixture("duplicated-usage.jsonl"), DEFAULT_ROOT_LABEL);
+        let mut usages: Vec<TimestampedUsage> = Vec::new();
+        for (i, line) in lines.iter().enumerate().take(2) {
+            usages.extend(session.apply_line(line, i as u64));
+        }
+        assert_eq!(session.usage.output_tokens, 120);
+
+        for (i, line) in lines.iter().enumerate().skip(2) {
+            usages.extend(session.apply_line(line, i as u64));
+        }
+        assert_eq!(usages.len(), 3);
+        assert_eq!(session.usage.output_tokens, 120 + 45 + 300);
+    }
+
+    #[test]
+    fn entries_without_ids_are_all_counted() {
+        let (session, usages, _) =
+            parse_session_file(&fixture("no-message-ids.jsonl"), DEFAULT_ROOT_LABEL).unwrap();
+
+        assert_eq!(usages.len(), 2);
+        assert_eq!(session.usage.input_tokens, 200);
+        assert_eq!(session.usage.output_tokens, 100);
+    }
+}
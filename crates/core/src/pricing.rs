@@ -0,0 +1,119 @@
+use crate::parser::{SessionData, TokenUsage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Prices for one model family, in USD per million tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Substring matched against the model id (e.g. "opus-4-5")
+    #[serde(rename = "match")]
+    pub pattern: String,
+    pub input: f64,
+    pub output: f64,
+    /// Cache creation (write) price
+    pub cache_write: f64,
+    /// Cache read price
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    fn new(pattern: &str, input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    /// Dollar cost of the given usage at these prices
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Pricing file format: a list of entries checked before the built-in table
+#[derive(Debug, Clone, Default, Deserialize)]
+struct PricingFile {
+    #[serde(default)]
+    models: Vec<ModelPricing>,
+}
+
+/// Ordered pricing table; the first entry whose pattern occurs in the model id wins
+#[derive(Debug, Clone, Serialize)]
+pub struct PricingTable {
+    pub models: Vec<ModelPricing>,
+}
+
+impl Default for PricingTable {
+    /// Anthropic API list prices
+    fn default() -> Self {
+        Self {
+            models: vec![
+                ModelPricing::new("opus-4-5", 5.0, 25.0, 6.25, 0.50),
+                ModelPricing::new("opus-4-6", 5.0, 25.0, 6.25, 0.50),
+                ModelPricing::new("opus", 15.0, 75.0, 18.75, 1.50),
+                ModelPricing::new("sonnet", 3.0, 15.0, 3.75, 0.30),
+                ModelPricing::new("3-5-haiku", 0.80, 4.0, 1.0, 0.08),
+                ModelPricing::new("3-haiku", 0.25, 1.25, 0.30, 0.03),
+                ModelPricing::new("haiku", 1.0, 5.0, 1.25, 0.10),
+            ],
+        }
+    }
+}
+
+impl PricingTable {
+    /// Load the built-in table with entries from `path` taking precedence.
+    /// A missing file is not an error.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut table = Self::default();
+
+        if !path.exists() {
+            return Ok(table);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let file: PricingFile = serde_json::from_str(&content)?;
+
+        let mut models = file.models;
+        models.append(&mut table.models);
+        table.models = models;
+
+        Ok(table)
+    }
+
+    /// Find the pricing entry for a model id
+    pub fn lookup(&self, model: &str) -> Option<&ModelPricing> {
+        let model = model.to_ascii_lowercase();
+        self.models
+            .iter()
+            .find(|p| model.contains(&p.pattern.to_ascii_lowercase()))
+    }
+
+    /// Dollar cost of usage for a model (0 for unknown models)
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> f64 {
+        self.lookup(model).map(|p| p.cost(usage)).unwrap_or(0.0)
+    }
+
+    /// Dollar cost of a per-model usage breakdown
+    pub fn cost_of_models(&self, models: &BTreeMap<String, TokenUsage>) -> f64 {
+        models
+            .iter()
+            .map(|(model, usage)| self.cost(model, usage))
+            .sum()
+    }
+
+    /// Fill in the cost of a session and of its tool calls
+    pub fn price_session(&self, session: &mut SessionData) {
+        session.cost_usd = self.cost_of_models(&session.models);
+        for tool in session.tools.values_mut() {
+            tool.cost_usd = self.cost_of_models(&tool.models);
+        }
+    }
+}
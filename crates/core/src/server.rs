@@ -0,0 +1,192 @@
+use serde::Deserialize;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::stats::Stats;
+
+/// How long a live-update stream may stay silent; the server sends a
+/// keep-alive comment well within this
+const STREAM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Contents of the PID file written by a running server
+#[derive(Debug, Clone, Copy)]
+pub struct PidFile {
+    pub pid: u32,
+    pub port: u16,
+}
+
+impl PidFile {
+    /// Read the PID file, removing it if the process it names is gone
+    pub fn read() -> Option<Self> {
+        let path = pid_file_path()?;
+        let content = fs::read_to_string(&path).ok()?;
+
+        let mut lines = content.lines();
+        let pid = lines.next()?.trim().parse().ok();
+        let port = lines.next().and_then(|p| p.trim().parse().ok());
+
+        match (pid, port) {
+            (Some(pid), Some(port)) if process_alive(pid) => Some(Self { pid, port }),
+            _ => {
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Record the current process as the running server
+    pub fn write_current(port: u16) -> std::io::Result<()> {
+        let Some(path) = pid_file_path() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, format!("{}\n{}\n", std::process::id(), port))
+    }
+
+    /// Remove the PID file if it still belongs to the current process
+    pub fn remove_current() {
+        let Some(path) = pid_file_path() else {
+            return;
+        };
+        let ours = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| c.lines().next().and_then(|l| l.trim().parse::<u32>().ok()))
+            == Some(std::process::id());
+        if ours {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+pub fn pid_file_path() -> Option<PathBuf> {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .map(|d| d.join("claude-monitor.pid"))
+}
+
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn process_alive(pid: u32) -> bool {
+    // tasklist prints an info line instead of a row when no process matches
+    let filter = format!("PID eq {}", pid);
+    match std::process::Command::new("tasklist")
+        .args(["/FI", &filter, "/FO", "CSV", "/NH"])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)),
+        // Without tasklist, assume it's running rather than drop a live server's PID file
+        Err(_) => true,
+    }
+}
+
+/// Update pushed by the server's `/api/events` stream
+#[derive(Debug, Deserialize)]
+struct StatsUpdate {
+    stats: Stats,
+}
+
+/// Fetch `/api/stats` from a server on localhost
+pub fn fetch_stats(port: u16) -> Result<Stats, Box<dyn std::error::Error>> {
+    let body = http_get(port, "/api/stats")?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Follow the `/api/events` stream of a server on localhost, calling `on_stats`
+/// with the current stats and then after every refresh. Returns when the server
+/// closes the stream or stops responding.
+pub fn subscribe_stats(
+    port: u16,
+    mut on_stats: impl FnMut(Stats),
+) -> Result<(), Box<dyn std::error::Error>> {
+    // HTTP/1.0 has no chunked encoding, so the event stream arrives as-is
+    // and ends when the connection closes
+    let mut stream = connect(port, STREAM_TIMEOUT)?;
+    write!(stream, "GET /api/events HTTP/1.0\r\nAccept: text/event-stream\r\n\r\n")?;
+    let mut reader = BufReader::new(stream);
+
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    check_status(&status)?;
+    // Skip the headers
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut event = String::new();
+    let mut data = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            // A blank line dispatches the event
+            if event == "stats" {
+                on_stats(serde_json::from_str::<StatsUpdate>(&data)?.stats);
+            }
+            event.clear();
+            data.clear();
+        } else if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim_start().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(value.strip_prefix(' ').unwrap_or(value));
+        }
+        // Anything else is a keep-alive comment or an unused field
+    }
+}
+
+/// Minimal blocking HTTP GET against localhost, returning the response body
+pub fn http_get(port: u16, path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut stream = connect(port, Duration::from_secs(5))?;
+
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nConnection: close\r\n\r\n",
+        path, port
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("malformed HTTP response")?;
+    check_status(head)?;
+
+    Ok(body.to_string())
+}
+
+fn connect(port: u16, read_timeout: Duration) -> std::io::Result<TcpStream> {
+    let addr = ([127, 0, 0, 1], port).into();
+    let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(2))?;
+    stream.set_read_timeout(Some(read_timeout))?;
+    Ok(stream)
+}
+
+/// Fail unless the response head starts with a 200 status line
+fn check_status(head: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let status_line = head.lines().next().unwrap_or(head);
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(status_line.to_string().into());
+    }
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use crate::blocks::{self, SessionBlock};
+use crate::burn::BurnRateInfo;
+use crate::parser::{
+    model_family, BudgetInfo, SessionData, TimestampedUsage, TokenUsage, DEFAULT_TOKEN_LIMIT,
+    ROLLING_WINDOW_HOURS,
+};
+use crate::pricing::PricingTable;
+
+/// Default seconds since last activity for a session to count as active
+pub const DEFAULT_ACTIVE_THRESHOLD_SECS: i64 = 300;
+
+/// Default burn rate intervals in minutes; the first one drives the exhaustion projection
+pub const DEFAULT_BURN_RATE_INTERVALS: [i64; 2] = [15, 60];
+
+/// Budget and activity settings the statistics are computed with
+#[derive(Debug, Clone)]
+pub struct StatsSettings {
+    /// Token limit per usage block
+    pub token_limit: u64,
+    /// Length of a usage block in hours
+    pub window_hours: i64,
+    /// Seconds since last activity for a session to count as active
+    pub active_threshold_secs: i64,
+    /// Burn rate intervals in minutes
+    pub burn_rate_intervals: Vec<i64>,
+}
+
+impl Default for StatsSettings {
+    fn default() -> Self {
+        Self {
+            token_limit: DEFAULT_TOKEN_LIMIT,
+            window_hours: ROLLING_WINDOW_HOURS,
+            active_threshold_secs: DEFAULT_ACTIVE_THRESHOLD_SECS,
+            burn_rate_intervals: DEFAULT_BURN_RATE_INTERVALS.to_vec(),
+        }
+    }
+}
+
+/// Summary statistics for the dashboard
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub total_usage: TokenUsage,
+    pub rolling_usage: TokenUsage,
+    /// Estimated lifetime cost in USD
+    pub total_cost_usd: f64,
+    /// Estimated cost in USD within the rolling window
+    pub rolling_cost_usd: f64,
+    /// Budget of the current usage block
+    pub budget: BudgetInfo,
+    /// Usage block that hasn't expired yet, if any
+    pub current_block: Option<SessionBlock>,
+    /// Recent burn rate and projected budget exhaustion
+    pub burn_rate: BurnRateInfo,
+    pub active_sessions: u32,
+    pub active_agents: u32,
+    pub total_messages: u32,
+    pub projects: Vec<ProjectStats>,
+    /// Usage per model, sorted by lifetime total descending
+    pub models: Vec<ModelStats>,
+    /// Models in `models` that no pricing entry matches; they cost $0
+    #[serde(default)]
+    pub unpriced_models: Vec<String>,
+    /// Usage per data root, in configuration order
+    pub roots: Vec<RootStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootStats {
+    pub label: String,
+    pub path: PathBuf,
+    /// Lifetime usage
+    pub usage: TokenUsage,
+    /// Usage within the rolling window
+    pub rolling_usage: TokenUsage,
+    pub cost_usd: f64,
+    pub rolling_cost_usd: f64,
+    pub session_count: u32,
+    pub message_count: u32,
+}
+
+impl RootStats {
+    pub fn new(label: String, path: PathBuf) -> Self {
+        Self {
+            label,
+            path,
+            usage: TokenUsage::default(),
+            rolling_usage: TokenUsage::default(),
+            cost_usd: 0.0,
+            rolling_cost_usd: 0.0,
+            session_count: 0,
+            message_count: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub path: String,
+    pub usage: TokenUsage,
+    pub models: BTreeMap<String, TokenUsage>,
+    pub cost_usd: f64,
+    pub session_count: u32,
+    pub message_count: u32,
+}
+
+impl ProjectStats {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            usage: TokenUsage::default(),
+            models: BTreeMap::new(),
+            cost_usd: 0.0,
+            session_count: 0,
+            message_count: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelStats {
+    pub model: String,
+    /// Model family (Opus, Sonnet, Haiku, Other)
+    pub family: String,
+    /// Lifetime usage
+    pub usage: TokenUsage,
+    /// Usage within the rolling window
+    pub rolling_usage: TokenUsage,
+    pub cost_usd: f64,
+    pub rolling_cost_usd: f64,
+}
+
+impl ModelStats {
+    pub fn new(model: String) -> Self {
+        Self {
+            family: model_family(&model).to_string(),
+            model,
+            usage: TokenUsage::default(),
+            rolling_usage: TokenUsage::default(),
+            cost_usd: 0.0,
+            rolling_cost_usd: 0.0,
+        }
+    }
+}
+
+/// Aggregate sessions and their timestamped usages into summary statistics.
+/// `roots` are the empty per-root totals to fill in; sessions of other roots
+/// are still counted in the overall totals.
+pub fn compute_stats<'a>(
+    sessions: impl IntoIterator<Item = (&'a SessionData, &'a [TimestampedUsage])>,
+    mut roots: Vec<RootStats>,
+    settings: &StatsSettings,
+    pricing: &PricingTable,
+    now: DateTime<Utc>,
+) -> Stats {
+    let mut total_usage = TokenUsage::default();
+    let mut total_cost_usd = 0.0;
+    let mut active_sessions = 0u32;
+    let mut active_agents = 0u32;
+    let mut total_messages = 0u32;
+    let mut project_map: HashMap<String, ProjectStats> = HashMap::new();
+    let mut model_map: HashMap<String, ModelStats> = HashMap::new();
+    let mut rolling_usage = TokenUsage::default();
+    let mut rolling_cost_usd = 0.0;
+    let mut usages: Vec<&TimestampedUsage> = Vec::new();
+
+    let window_start = now - Duration::hours(settings.window_hours);
+
+    for (session, session_usages) in sessions {
+        let mut root_stats = roots.iter_mut().find(|r| r.label == session.root);
+        if let Some(root_stats) = root_stats.as_deref_mut() {
+            root_stats.usage += session.usage.clone();
+            root_stats.cost_usd += session.cost_usd;
+            root_stats.session_count += 1;
+            root_stats.message_count += session.message_count;
+        }
+
+        total_usage += session.usage.clone();
+        total_cost_usd += session.cost_usd;
+        total_messages += session.message_count;
+
+        // Check if session is active (recent last activity)
+        let is_active = session
+            .last_activity
+            .map(|la| (now - la).num_seconds() < settings.active_threshold_secs)
+            .unwrap_or(false);
+
+        if is_active {
+            if session.is_agent {
+                active_agents += 1;
+            } else {
+                active_sessions += 1;
+            }
+        }
+
+        // Aggregate by project
+        let entry = project_map
+            .entry(session.project_path.clone())
+            .or_insert_with(|| ProjectStats::new(session.project_path.clone()));
+        entry.usage += session.usage.clone();
+        entry.cost_usd += session.cost_usd;
+        entry.session_count += 1;
+        entry.message_count += session.message_count;
+
+        // Aggregate by model
+        for (model, usage) in &session.models {
+            *entry.models.entry(model.clone()).or_default() += usage.clone();
+            model_map
+                .entry(model.clone())
+                .or_insert_with(|| ModelStats::new(model.clone()))
+                .usage += usage.clone();
+        }
+
+        // Rolling window usage
+        for tu in session_usages
+            .iter()
+            .filter(|tu| tu.timestamp >= window_start)
+        {
+            let cost = pricing.cost(&tu.model, &tu.usage);
+            rolling_usage += tu.usage.clone();
+            rolling_cost_usd += cost;
+
+            if let Some(root_stats) = root_stats.as_deref_mut() {
+                root_stats.rolling_usage += tu.usage.clone();
+                root_stats.rolling_cost_usd += cost;
+            }
+
+            let model_stats = model_map
+                .entry(tu.model.clone())
+                .or_insert_with(|| ModelStats::new(tu.model.clone()));
+            model_stats.rolling_usage += tu.usage.clone();
+            model_stats.rolling_cost_usd += cost;
+        }
+        usages.extend(session_usages);
+    }
+
+    // The limit resets when the current block expires
+    let current_block =
+        blocks::current_block(usages.iter().copied(), settings.window_hours, pricing, now);
+    let budget = BudgetInfo::new(
+        current_block
+            .as_ref()
+            .map(|b| b.usage.billable())
+            .unwrap_or(0),
+        settings.token_limit,
+        current_block.as_ref().map(|b| b.end),
+        settings.window_hours,
+    );
+    let burn_rate = BurnRateInfo::new(usages, &settings.burn_rate_intervals, &budget, now);
+
+    let mut projects: Vec<ProjectStats> = project_map.into_values().collect();
+
+    // Sort by total tokens descending
+    projects.sort_by_key(|p| std::cmp::Reverse(p.usage.total()));
+
+    // Skip models that never reported tokens (e.g. synthetic messages)
+    let mut models: Vec<ModelStats> = model_map
+        .into_values()
+        .filter(|m| m.usage.total() > 0 || m.rolling_usage.total() > 0)
+        .map(|mut m| {
+            m.cost_usd = pricing.cost(&m.model, &m.usage);
+            m
+        })
+        .collect();
+    models.sort_by_key(|m| std::cmp::Reverse(m.usage.total()));
+    let unpriced_models = models
+        .iter()
+        .filter(|m| pricing.lookup(&m.model).is_none())
+        .map(|m| m.model.clone())
+        .collect();
+
+    Stats {
+        total_usage,
+        rolling_usage,
+        total_cost_usd,
+        rolling_cost_usd,
+        budget,
+        current_block,
+        burn_rate,
+        active_sessions,
+        active_agents,
+        total_messages,
+        projects,
+        models,
+        unpriced_models,
+        roots,
+    }
+}
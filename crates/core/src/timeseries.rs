@@ -0,0 +1,237 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::parser::TokenUsage;
+
+/// Most buckets a series may span, so a wide range of small buckets can't
+/// exhaust memory
+pub const MAX_BUCKETS: usize = 5_000;
+
+/// Length of a time-series bucket. Boundaries follow local time; weeks start on Monday.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Bucket {
+    /// Start of the bucket containing `ts`
+    pub fn floor(self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        let local = ts.with_timezone(&Local);
+        let date = local.date_naive();
+        match self {
+            // Subtract rather than rebuild the local time, so the hour repeated
+            // when DST ends stays two buckets
+            Bucket::Hour => {
+                let into_hour = local.minute() as i64 * 60 + local.second() as i64;
+                ts - Duration::seconds(into_hour) - Duration::nanoseconds(local.nanosecond() as i64)
+            }
+            Bucket::Day => local_start(date, 0),
+            Bucket::Week => local_start(
+                date - Duration::days(date.weekday().num_days_from_monday() as i64),
+                0,
+            ),
+            Bucket::Month => local_start(date.with_day(1).unwrap_or(date), 0),
+        }
+    }
+
+    /// Start of the bucket following the one starting at `start`
+    pub fn next(self, start: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Bucket::Hour => start + Duration::hours(1),
+            // Step in local dates so DST changes don't shift the boundaries
+            Bucket::Day => self.floor(start + Duration::hours(36)),
+            Bucket::Week => self.floor(start + Duration::days(7) + Duration::hours(12)),
+            Bucket::Month => self.floor(start + Duration::days(32)),
+        }
+    }
+
+    /// Name of the bucket length, as in query parameters
+    pub fn name(self) -> &'static str {
+        match self {
+            Bucket::Hour => "hour",
+            Bucket::Day => "day",
+            Bucket::Week => "week",
+            Bucket::Month => "month",
+        }
+    }
+
+    /// Human-readable name of the bucket starting at `start` ("2025-01-31", "2025-W05", "2025-01")
+    pub fn label(self, start: DateTime<Utc>) -> String {
+        let local = start.with_timezone(&Local);
+        match self {
+            Bucket::Hour => local.format("%Y-%m-%d %H:00").to_string(),
+            Bucket::Day => local.format("%Y-%m-%d").to_string(),
+            Bucket::Week => local.format("%G-W%V").to_string(),
+            Bucket::Month => local.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// First instant of `hour` on a local date, in UTC
+fn local_start(date: NaiveDate, hour: u32) -> DateTime<Utc> {
+    let naive = date.and_hms_opt(hour, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        // Hour skipped by a DST change
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+        .with_timezone(&Utc)
+}
+
+/// Dimension usage within each bucket is split by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Project,
+    Model,
+    /// Main session vs agent
+    SessionType,
+}
+
+/// Usage within one bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeBucket {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    pub requests: u32,
+    /// Usage per group, when grouped
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, TokenUsage>,
+}
+
+/// Bucketed usage over a time range
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeseries {
+    pub bucket: Bucket,
+    pub group_by: Option<GroupBy>,
+    /// Start of the range (inclusive)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (exclusive)
+    pub to: Option<DateTime<Utc>>,
+    /// Consecutive buckets, including empty ones, oldest first
+    pub buckets: Vec<TimeBucket>,
+}
+
+impl Timeseries {
+    pub fn new(
+        bucket: Bucket,
+        group_by: Option<GroupBy>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            bucket,
+            group_by,
+            from,
+            to,
+            buckets: Vec::new(),
+        }
+    }
+
+    /// Add usage at `ts` (already filtered to the range), optionally under a group key
+    pub fn add(
+        &mut self,
+        ts: DateTime<Utc>,
+        usage: &TokenUsage,
+        cost_usd: f64,
+        group: Option<&str>,
+    ) {
+        let start = self.bucket.floor(ts);
+        let index = match self.buckets.binary_search_by_key(&start, |b| b.start) {
+            Ok(i) => i,
+            Err(i) => {
+                self.buckets.insert(i, self.empty_bucket(start));
+                i
+            }
+        };
+
+        let bucket = &mut self.buckets[index];
+        bucket.usage += usage.clone();
+        bucket.cost_usd += cost_usd;
+        bucket.requests += 1;
+        if let Some(group) = group {
+            *bucket.groups.entry(group.to_string()).or_default() += usage.clone();
+        }
+    }
+
+    /// Fill gaps with empty buckets, spanning the requested range where given.
+    /// Fails if that takes more than [`MAX_BUCKETS`] buckets.
+    pub fn fill_gaps(&mut self) -> Result<(), String> {
+        let first = self
+            .from
+            .map(|f| self.bucket.floor(f))
+            .or_else(|| self.buckets.first().map(|b| b.start));
+        let last = self
+            .to
+            .map(|t| self.bucket.floor(t - Duration::nanoseconds(1)))
+            .or_else(|| self.buckets.last().map(|b| b.start));
+        let (Some(first), Some(last)) = (first, last) else {
+            return Ok(());
+        };
+
+        let mut count = 0;
+        let mut start = first;
+        while start <= last {
+            count += 1;
+            if count > MAX_BUCKETS {
+                return Err(format!(
+                    "range spans more than {} {} buckets, use a shorter range or longer buckets",
+                    MAX_BUCKETS,
+                    self.bucket.name()
+                ));
+            }
+            start = self.bucket.next(start);
+        }
+
+        let mut existing = std::mem::take(&mut self.buckets).into_iter().peekable();
+        let mut start = first;
+        while start <= last {
+            match existing.peek() {
+                Some(b) if b.start == start => self.buckets.extend(existing.next()),
+                _ => self.buckets.push(self.empty_bucket(start)),
+            }
+            start = self.bucket.next(start);
+        }
+        Ok(())
+    }
+
+    fn empty_bucket(&self, start: DateTime<Utc>) -> TimeBucket {
+        TimeBucket {
+            start,
+            end: self.bucket.next(start),
+            usage: TokenUsage::default(),
+            cost_usd: 0.0,
+            requests: 0,
+            groups: BTreeMap::new(),
+        }
+    }
+}
+
+/// Parse a range bound: RFC 3339, or a local date (`2025-01-31`)
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| local_start(date, 0))
+        .map_err(|_| format!("invalid time \"{}\", expected RFC 3339 or YYYY-MM-DD", value))
+}
+
+/// Parse an end bound: a local date includes the whole day, an exact time is exclusive
+pub fn parse_end(value: &str) -> Result<DateTime<Utc>, String> {
+    let end = parse_time(value)?;
+    if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+        Ok(Bucket::Day.next(end))
+    } else {
+        Ok(end)
+    }
+}
@@ -0,0 +1,62 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::parser::{SessionData, TokenUsage};
+
+/// Invocations of one tool across sessions
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSummary {
+    pub name: String,
+    pub calls: u32,
+    pub errors: u32,
+    /// Share of calls that failed, 0 to 1
+    pub failure_rate: f64,
+    /// Usage of the turns that called the tool
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    /// Sessions that called the tool
+    pub session_count: u32,
+}
+
+impl ToolSummary {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            calls: 0,
+            errors: 0,
+            failure_rate: 0.0,
+            usage: TokenUsage::default(),
+            cost_usd: 0.0,
+            session_count: 0,
+        }
+    }
+}
+
+/// Sum the tool calls of `sessions` per tool, most called first
+pub fn summarize<'a>(sessions: impl IntoIterator<Item = &'a SessionData>) -> Vec<ToolSummary> {
+    let mut tools: BTreeMap<&str, ToolSummary> = BTreeMap::new();
+    for session in sessions {
+        for (name, stats) in &session.tools {
+            let summary = tools
+                .entry(name.as_str())
+                .or_insert_with(|| ToolSummary::new(name.clone()));
+            summary.calls += stats.calls;
+            summary.errors += stats.errors;
+            summary.usage += stats.usage.clone();
+            summary.cost_usd += stats.cost_usd;
+            summary.session_count += 1;
+        }
+    }
+
+    let mut tools: Vec<ToolSummary> = tools
+        .into_values()
+        .map(|mut t| {
+            if t.calls > 0 {
+                t.failure_rate = t.errors as f64 / t.calls as f64;
+            }
+            t
+        })
+        .collect();
+    tools.sort_by_key(|t| std::cmp::Reverse(t.calls));
+    tools
+}
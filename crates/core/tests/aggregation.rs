@@ -0,0 +1,289 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::path::PathBuf;
+
+use claude_monitor_core::blocks::{compute_blocks, current_block, recent_blocks};
+use claude_monitor_core::burn::BurnRateInfo;
+use claude_monitor_core::parser::{BudgetInfo, SessionData, TimestampedUsage, TokenUsage};
+use claude_monitor_core::pricing::PricingTable;
+use claude_monitor_core::stats::{compute_stats, RootStats, StatsSettings};
+use claude_monitor_core::tools;
+
+const SONNET: &str = "claude-sonnet-4-5-20250929";
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 9, 1, 12, 0, 0).unwrap()
+}
+
+fn usage(input: u64, output: u64) -> TokenUsage {
+    TokenUsage {
+        input_tokens: input,
+        output_tokens: output,
+        ..Default::default()
+    }
+}
+
+fn at(minutes_ago: i64, input: u64, output: u64) -> TimestampedUsage {
+    TimestampedUsage {
+        timestamp: now() - Duration::minutes(minutes_ago),
+        usage: usage(input, output),
+        model: SONNET.to_string(),
+        message_key: format!("msg-{}", minutes_ago),
+        git_branch: None,
+    }
+}
+
+fn session(root: &str, project: &str, id: &str, usages: &[TimestampedUsage]) -> SessionData {
+    let path = PathBuf::from(format!("/projects/{}/{}.jsonl", project, id));
+    let mut session = SessionData::from_path(&path, root);
+    session.project_path = project.to_string();
+    for tu in usages {
+        session.usage += tu.usage.clone();
+        *session.models.entry(tu.model.clone()).or_default() += tu.usage.clone();
+        session.message_count += 1;
+        session.last_activity = session.last_activity.max(Some(tu.timestamp));
+    }
+    session
+}
+
+#[test]
+fn blocks_start_at_first_message_after_expiry() {
+    let usages = [
+        at(600, 10, 1),
+        at(500, 20, 2),
+        at(200, 30, 3),
+        at(10, 40, 4),
+    ];
+    let blocks = compute_blocks(&usages, 5, &PricingTable::default(), now());
+
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].start, now() - Duration::minutes(600));
+    assert_eq!(blocks[0].request_count, 2);
+    assert!(!blocks[0].is_active);
+
+    assert_eq!(blocks[1].start, now() - Duration::minutes(200));
+    assert_eq!(blocks[1].end, now() + Duration::minutes(100));
+    assert_eq!(blocks[1].usage.billable(), 30 + 3 + 40 + 4);
+    assert!(blocks[1].is_active);
+}
+
+#[test]
+fn blocks_end_exactly_after_their_length() {
+    // 300 minutes ago starts a block that ends right now; the usage at its
+    // end belongs to the next one, the one a second earlier doesn't
+    let mut last_second = at(0, 2, 0);
+    last_second.timestamp = now() - Duration::seconds(1);
+    let usages = [at(300, 1, 0), last_second, at(0, 4, 0)];
+    let blocks = compute_blocks(&usages, 5, &PricingTable::default(), now());
+
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].end, now());
+    assert_eq!(blocks[0].usage.input_tokens, 1 + 2);
+    assert_eq!(blocks[0].last_activity, now() - Duration::seconds(1));
+    assert!(!blocks[0].is_active);
+    assert_eq!(blocks[1].start, now());
+    assert_eq!(blocks[1].usage.input_tokens, 4);
+}
+
+#[test]
+fn gaps_between_blocks_are_not_filled() {
+    // Back to back blocks, then a day without usage
+    let usages = [
+        at(2_000, 1, 0),
+        at(1_700, 2, 0),
+        at(1_500, 4, 0),
+        at(30, 8, 0),
+    ];
+    let blocks = compute_blocks(&usages, 5, &PricingTable::default(), now());
+
+    let starts: Vec<i64> = blocks
+        .iter()
+        .map(|b| (now() - b.start).num_minutes())
+        .collect();
+    assert_eq!(starts, vec![2_000, 1_700, 30]);
+    assert_eq!(blocks[1].usage.input_tokens, 2 + 4);
+    assert_eq!(blocks[1].end, now() - Duration::minutes(1_400));
+}
+
+#[test]
+fn recent_blocks_match_the_full_history() {
+    let usages: Vec<TimestampedUsage> = (0..40).map(|i| at(i * 97, i as u64, 1)).collect();
+    let pricing = PricingTable::default();
+    let all = compute_blocks(&usages, 5, &pricing, now());
+
+    let recent = recent_blocks(&usages, 5, &pricing, now(), 3);
+    assert_eq!(recent.len(), 3);
+    for (recent, full) in recent.iter().zip(&all[all.len() - 3..]) {
+        assert_eq!(recent.start, full.start);
+        assert_eq!(recent.usage.total(), full.usage.total());
+        assert_eq!(recent.request_count, full.request_count);
+        assert_eq!(recent.cost_usd, full.cost_usd);
+    }
+    assert_eq!(
+        recent_blocks(&usages, 5, &pricing, now(), 100).len(),
+        all.len()
+    );
+
+    let current = current_block(&usages, 5, &pricing, now()).expect("active block");
+    assert_eq!(current.start, all.last().unwrap().start);
+    assert!(current_block(&usages, 5, &pricing, now() + Duration::hours(5)).is_none());
+    assert!(current_block(&[], 5, &pricing, now()).is_none());
+}
+
+#[test]
+fn burn_rate_projects_exhaustion_before_reset() {
+    let usages = [at(5, 1_000, 500), at(20, 9_000, 0)];
+    let budget = BudgetInfo::new(10_000, 20_000, Some(now() + Duration::hours(2)), 5);
+    let burn = BurnRateInfo::new(&usages, &[15, 60], &budget, now());
+
+    assert_eq!(burn.rates.len(), 2);
+    assert_eq!(burn.tokens_per_minute, 1_500.0 / 15.0);
+    assert_eq!(burn.rates[1].tokens_per_minute, 10_500.0 / 60.0);
+    // 10,000 remaining at 100 tokens per minute
+    assert_eq!(burn.minutes_to_exhaustion, Some(100));
+    assert!(!burn.reaches_reset);
+
+    let idle = BurnRateInfo::new(&[], &[15], &budget, now());
+    assert_eq!(idle.minutes_to_exhaustion, None);
+    assert!(idle.reaches_reset);
+}
+
+#[test]
+fn burn_rate_handles_idle_exhausted_and_huge_budgets() {
+    let usages = [at(5, 1_000, 500)];
+
+    let idle = BudgetInfo::new(0, 10_000, None, 5);
+    let burn = BurnRateInfo::new(&[], &[15], &idle, now());
+    assert_eq!(burn.tokens_per_minute, 0.0);
+    assert_eq!(burn.exhaustion_time, None);
+    assert!(burn.reaches_reset);
+
+    // Nothing left: exhausted right now, even without any recent usage
+    let exhausted = BudgetInfo::new(10_000, 10_000, Some(now() + Duration::hours(1)), 5);
+    let burn = BurnRateInfo::new(&[], &[15], &exhausted, now());
+    assert_eq!(burn.exhaustion_time, Some(now()));
+    assert_eq!(burn.minutes_to_exhaustion, Some(0));
+
+    // Lasting longer than any representable date isn't an overflow
+    let huge = BudgetInfo::new(0, u64::MAX, None, 5);
+    let burn = BurnRateInfo::new(&usages, &[i64::MAX / 60, 15], &huge, now());
+    assert_eq!(
+        burn.rates[0].tokens_per_minute,
+        1_500.0 / (i64::MAX / 60) as f64
+    );
+    assert_eq!(burn.minutes_to_exhaustion, None);
+    assert!(burn.reaches_reset);
+
+    let burn = BurnRateInfo::new(&usages, &[i64::MAX], &huge, now());
+    assert_eq!(burn.exhaustion_time, None);
+}
+
+#[test]
+fn stats_sum_sessions_projects_and_roots() {
+    let a = [at(30, 100, 10), at(400, 1_000, 100)];
+    let b = [at(2, 50, 5)];
+    let c = [at(1, 7, 7)];
+    let mut agent = session("work", "/src/api", "b", &b);
+    agent.is_agent = true;
+    let sessions = [
+        (session("work", "/src/api", "a", &a), &a[..]),
+        (agent, &b[..]),
+        (session("personal", "/src/blog", "c", &c), &c[..]),
+    ];
+
+    let roots = vec![
+        RootStats::new("work".to_string(), PathBuf::from("/work")),
+        RootStats::new("personal".to_string(), PathBuf::from("/personal")),
+    ];
+    let settings = StatsSettings {
+        token_limit: 1_000,
+        ..Default::default()
+    };
+    let stats = compute_stats(
+        sessions.iter().map(|(s, u)| (s, *u)),
+        roots,
+        &settings,
+        &PricingTable::default(),
+        now(),
+    );
+
+    assert_eq!(stats.total_usage.total(), 1_210 + 55 + 14);
+    assert_eq!(stats.total_messages, 4);
+    // The usage from 400 minutes ago is outside the 5 hour window
+    assert_eq!(stats.rolling_usage.total(), 110 + 55 + 14);
+    assert_eq!(stats.active_sessions, 1);
+    assert_eq!(stats.active_agents, 1);
+
+    assert_eq!(stats.projects.len(), 2);
+    assert_eq!(stats.projects[0].path, "/src/api");
+    assert_eq!(stats.projects[0].session_count, 2);
+
+    assert_eq!(stats.roots[0].session_count, 2);
+    assert_eq!(stats.roots[0].rolling_usage.total(), 110 + 55);
+    assert_eq!(stats.roots[1].usage.total(), 14);
+
+    assert_eq!(stats.models.len(), 1);
+    assert_eq!(stats.models[0].family, "Sonnet");
+    assert!(stats.unpriced_models.is_empty());
+
+    // The current block started 30 minutes ago
+    let block = stats.current_block.expect("active block");
+    assert_eq!(block.start, now() - Duration::minutes(30));
+    assert_eq!(stats.budget.used, 110 + 55 + 14);
+    assert_eq!(stats.budget.remaining, 1_000 - 179);
+    assert_eq!(stats.budget.reset_time, Some(block.end));
+}
+
+#[test]
+fn stats_of_nothing_are_empty() {
+    let stats = compute_stats(
+        std::iter::empty(),
+        Vec::new(),
+        &StatsSettings::default(),
+        &PricingTable::default(),
+        now(),
+    );
+
+    assert_eq!(stats.total_usage.total(), 0);
+    assert!(stats.current_block.is_none());
+    assert_eq!(stats.budget.used, 0);
+    assert_eq!(stats.budget.percentage, 0.0);
+    assert!(stats.projects.is_empty());
+}
+
+#[test]
+fn models_without_a_price_are_listed() {
+    let mut unknown = at(5, 100, 10);
+    unknown.model = "gpt-4".to_string();
+    let usages = [at(10, 100, 10), unknown];
+    let sessions = [(session("default", "/src/api", "a", &usages), &usages[..])];
+    let stats = compute_stats(
+        sessions.iter().map(|(s, u)| (s, *u)),
+        Vec::new(),
+        &StatsSettings::default(),
+        &PricingTable::default(),
+        now(),
+    );
+
+    assert_eq!(stats.models.len(), 2);
+    assert_eq!(stats.unpriced_models, vec!["gpt-4".to_string()]);
+}
+
+#[test]
+fn tool_summaries_merge_sessions() {
+    let mut a = session("default", "/src/api", "a", &[]);
+    let mut b = session("default", "/src/api", "b", &[]);
+    for (session, calls, errors) in [(&mut a, 3, 1), (&mut b, 1, 1)] {
+        let bash = session.tools.entry("Bash".to_string()).or_default();
+        bash.calls = calls;
+        bash.errors = errors;
+    }
+    b.tools.entry("Read".to_string()).or_default().calls = 2;
+
+    let summaries = tools::summarize([&a, &b]);
+    assert_eq!(summaries[0].name, "Bash");
+    assert_eq!(summaries[0].calls, 4);
+    assert_eq!(summaries[0].session_count, 2);
+    assert_eq!(summaries[0].failure_rate, 0.5);
+    assert_eq!(summaries[1].name, "Read");
+    assert_eq!(summaries[1].failure_rate, 0.0);
+}
@@ -0,0 +1,59 @@
+use std::fs;
+use std::path::PathBuf;
+
+use claude_monitor_core::config::{load_roots, CLAUDE_CONFIG_DIR_VAR, CONFIG_VAR, ROOTS_VAR};
+
+/// One test, since the roots come from process-wide environment variables
+#[test]
+fn roots_resolve_like_the_server() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_CONFIG_HOME", dir.path());
+    std::env::remove_var(CONFIG_VAR);
+    std::env::remove_var(ROOTS_VAR);
+    std::env::set_var(CLAUDE_CONFIG_DIR_VAR, "/data/.claude-work");
+    let roots = || -> Vec<(String, PathBuf)> {
+        load_roots()
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.label, r.path))
+            .collect()
+    };
+
+    // Without a config file CLAUDE_CONFIG_DIR replaces the default
+    assert_eq!(
+        roots(),
+        vec![(
+            "claude-work".to_string(),
+            PathBuf::from("/data/.claude-work")
+        )]
+    );
+
+    // The config file's roots win; its server settings are ignored here
+    let config = dir.path().join("claude-monitor");
+    fs::create_dir_all(&config).unwrap();
+    fs::write(
+        config.join("config.toml"),
+        "port = 4000\n\n[[roots]]\nlabel = \"personal\"\npath = \"/data/personal\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        roots(),
+        vec![("personal".to_string(), PathBuf::from("/data/personal"))]
+    );
+
+    // And CLAUDE_MONITOR_ROOTS wins over the file
+    std::env::set_var(ROOTS_VAR, "a=/data/a,b=/data/b");
+    assert_eq!(
+        roots(),
+        vec![
+            ("a".to_string(), PathBuf::from("/data/a")),
+            ("b".to_string(), PathBuf::from("/data/b")),
+        ]
+    );
+
+    // Another config file can be selected
+    std::env::remove_var(ROOTS_VAR);
+    fs::write(dir.path().join("other.toml"), "").unwrap();
+    std::env::set_var(CONFIG_VAR, dir.path().join("other.toml"));
+    assert_eq!(roots()[0].0, "claude-work");
+}
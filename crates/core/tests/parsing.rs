@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use claude_monitor_core::parser::project::{decode_project_dir, encode_project_dir};
+use claude_monitor_core::parser::session::{
+    parse_session_file, parse_session_messages, parse_session_tail, session_files,
+};
+use claude_monitor_core::parser::{SessionData, DEFAULT_ROOT_LABEL};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[test]
+fn transcript_records_project_branch_and_tools() {
+    let (session, usages, offset) =
+        parse_session_file(&fixture("duplicated-usage.jsonl"), DEFAULT_ROOT_LABEL).unwrap();
+
+    assert_eq!(session.root, DEFAULT_ROOT_LABEL);
+    assert_eq!(session.cwd.as_deref(), Some("/Users/dev/my-app"));
+    assert_eq!(session.git_branch.as_deref(), Some("main"));
+    assert!(usages
+        .iter()
+        .all(|tu| tu.git_branch.as_deref() == Some("main")));
+    assert_eq!(
+        offset,
+        fs::metadata(fixture("duplicated-usage.jsonl"))
+            .unwrap()
+            .len()
+    );
+
+    let read = session.tools.get("Read").expect("Read tool call");
+    assert_eq!(read.calls, 1);
+    assert_eq!(read.errors, 0);
+}
+
+#[test]
+fn tools_of_a_turn_share_its_usage_once() {
+    let fixture = fs::read_to_string(fixture("duplicated-usage.jsonl")).unwrap();
+    let failed = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_02","is_error":true},{"type":"tool_result","tool_use_id":"toolu_unknown","is_error":true}]},"timestamp":"2025-09-01T10:05:00.000Z"}"#;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    fs::write(&path, format!("{}{}\n", fixture, failed)).unwrap();
+
+    let (session, _, _) = parse_session_file(&path, DEFAULT_ROOT_LABEL).unwrap();
+    let (read, grep) = (&session.tools["Read"], &session.tools["Grep"]);
+    // Both were called in the turn that also wrote a text block
+    assert_eq!(read.usage.input_tokens, 3);
+    assert_eq!(grep.usage.input_tokens, 3);
+    assert_eq!((read.errors, grep.errors), (0, 1));
+    // Results for unknown calls are ignored
+    let errors: u32 = session.tools.values().map(|t| t.errors).sum();
+    assert_eq!(errors, 1);
+}
+
+#[test]
+fn messages_are_listed_once_per_request() {
+    let messages = parse_session_messages(&fixture("duplicated-usage.jsonl")).unwrap();
+
+    let assistant: Vec<_> = messages.iter().filter(|m| m.role == "assistant").collect();
+    assert_eq!(assistant.len(), 3);
+    assert!(assistant.iter().all(|m| m.usage.is_some()));
+    assert!(messages
+        .windows(2)
+        .all(|w| w[0].timestamp <= w[1].timestamp));
+}
+
+#[test]
+fn session_files_skip_other_files() {
+    let dir = std::env::temp_dir().join(format!("claude-monitor-core-{}", std::process::id()));
+    let project = dir.join("-Users-dev-my-app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("a.jsonl"), "").unwrap();
+    fs::write(project.join("notes.txt"), "").unwrap();
+    fs::write(dir.join("stray.jsonl"), "").unwrap();
+
+    let files = session_files(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(files, vec![project.join("a.jsonl")]);
+}
+
+#[test]
+fn project_dirs_encode_every_separator() {
+    assert_eq!(
+        encode_project_dir("/Users/me/my-app.v2"),
+        "-Users-me-my-app-v2"
+    );
+}
+
+#[test]
+fn project_dirs_decode_to_existing_directories() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("Users/dev/my-app")).unwrap();
+    fs::create_dir_all(root.path().join("Users/dev/my/other")).unwrap();
+    fs::create_dir_all(root.path().join("Users/dev/a-b/c-d.e")).unwrap();
+
+    let decode = |name| decode_project_dir(name, root.path());
+    let under_root = |path: &str| root.path().join(path).to_string_lossy().into_owned();
+    assert_eq!(decode("-Users-dev-my-app"), under_root("Users/dev/my-app"));
+    assert_eq!(
+        decode("-Users-dev-my-other"),
+        under_root("Users/dev/my/other")
+    );
+    assert_eq!(
+        decode("-Users-dev-a-b-c-d-e"),
+        under_root("Users/dev/a-b/c-d.e")
+    );
+}
+
+#[test]
+fn missing_projects_read_every_separator_as_a_slash() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("Users/dev")).unwrap();
+
+    assert_eq!(
+        decode_project_dir("-Users-dev-gone-app", root.path()),
+        "/Users/dev/gone/app"
+    );
+}
+
+fn assistant_line(minute: u32, input: u64) -> String {
+    format!(
+        r#"{{"type":"assistant","message":{{"role":"assistant","model":"claude-sonnet-4-5","usage":{{"input_tokens":{},"output_tokens":1}}}},"timestamp":"2025-09-01T09:{:02}:00.000Z"}}"#,
+        input, minute
+    ) + "\n"
+}
+
+#[test]
+fn tail_parsing_resumes_after_the_last_complete_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let first = assistant_line(0, 100);
+    let second = assistant_line(1, 200);
+    let (half, rest) = second.split_at(second.len() / 2);
+    fs::write(&path, format!("{}{}", first, half)).unwrap();
+
+    // The half-written line is left for the next call
+    let mut session = SessionData::from_path(&path, DEFAULT_ROOT_LABEL);
+    let (usages, offset) = parse_session_tail(&path, &mut session, 0).unwrap();
+    assert_eq!(usages.len(), 1);
+    assert_eq!(offset, first.len() as u64);
+
+    let (usages, same) = parse_session_tail(&path, &mut session, offset).unwrap();
+    assert!(usages.is_empty());
+    assert_eq!(same, offset);
+
+    fs::write(&path, format!("{}{}{}", first, half, rest)).unwrap();
+    let (usages, offset) = parse_session_tail(&path, &mut session, offset).unwrap();
+    assert_eq!(usages.len(), 1);
+    assert_eq!(offset, (first.len() + second.len()) as u64);
+    assert_eq!(session.usage.input_tokens, 300);
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use claude_monitor_core::parser::TokenUsage;
+use claude_monitor_core::pricing::PricingTable;
+
+fn million_each() -> TokenUsage {
+    TokenUsage {
+        input_tokens: 1_000_000,
+        output_tokens: 1_000_000,
+        cache_creation_input_tokens: 1_000_000,
+        cache_read_input_tokens: 1_000_000,
+    }
+}
+
+#[test]
+fn pricing_matches_the_first_pattern() {
+    let pricing = PricingTable::default();
+    let usage = TokenUsage {
+        cache_creation_input_tokens: 0,
+        ..million_each()
+    };
+
+    assert_eq!(
+        pricing.cost("claude-opus-4-5-20251101", &usage),
+        5.0 + 25.0 + 0.5
+    );
+    assert_eq!(
+        pricing.cost("claude-opus-4-1-20250805", &usage),
+        15.0 + 75.0 + 1.5
+    );
+    assert_eq!(
+        pricing.cost("claude-3-5-haiku-20241022", &usage),
+        0.8 + 4.0 + 0.08
+    );
+    assert_eq!(pricing.cost("gpt-4", &usage), 0.0);
+}
+
+#[test]
+fn patterns_match_case_insensitive_substrings_only() {
+    let pricing = PricingTable::default();
+
+    assert_eq!(
+        pricing.lookup("Claude-Opus-4-6").unwrap().pattern,
+        "opus-4-6"
+    );
+    // "opus-4-5" is not a substring of an opus-4-6 id, and vice versa
+    assert_eq!(
+        pricing.lookup("claude-opus-4-6-20260101").unwrap().pattern,
+        "opus-4-6"
+    );
+    assert_eq!(pricing.lookup("claude-opus-4").unwrap().pattern, "opus");
+    assert!(pricing.lookup("<synthetic>").is_none());
+}
+
+#[test]
+fn cost_weights_every_token_kind() {
+    let pricing = PricingTable::default();
+    let usage = TokenUsage {
+        input_tokens: 2_000_000,
+        output_tokens: 500_000,
+        cache_creation_input_tokens: 100_000,
+        cache_read_input_tokens: 10_000_000,
+    };
+
+    let cost = pricing.cost("claude-sonnet-4-5", &usage);
+    let expected = 2.0 * 3.0 + 0.5 * 15.0 + 0.1 * 3.75 + 10.0 * 0.30;
+    assert!((cost - expected).abs() < 1e-9);
+
+    let mut models = BTreeMap::new();
+    models.insert("claude-sonnet-4-5".to_string(), usage.clone());
+    models.insert("claude-haiku-4-5".to_string(), million_each());
+    models.insert("unknown-model".to_string(), million_each());
+    let total = pricing.cost_of_models(&models);
+    assert!((total - (expected + 1.0 + 5.0 + 1.25 + 0.10)).abs() < 1e-9);
+}
+
+#[test]
+fn missing_pricing_file_uses_the_built_in_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let pricing = PricingTable::load(&dir.path().join("pricing.json")).unwrap();
+    assert_eq!(
+        pricing.models.len(),
+        PricingTable::default().models.len()
+    );
+}
+
+#[test]
+fn pricing_file_entries_take_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pricing.json");
+    fs::write(
+        &path,
+        r#"{"models": [
+            {"match": "sonnet-4-5", "input": 1.0, "output": 2.0, "cache_write": 0.0, "cache_read": 0.0},
+            {"match": "gpt", "input": 10.0, "output": 0.0, "cache_write": 0.0, "cache_read": 0.0}
+        ]}"#,
+    )
+    .unwrap();
+    let pricing = PricingTable::load(&path).unwrap();
+    let usage = TokenUsage {
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: 0,
+        ..million_each()
+    };
+
+    // The override wins over the built-in "sonnet" entry, which still applies elsewhere
+    assert_eq!(pricing.cost("claude-sonnet-4-5", &usage), 3.0);
+    assert_eq!(pricing.cost("claude-sonnet-4", &usage), 18.0);
+    assert_eq!(pricing.cost("gpt-4", &usage), 10.0);
+}
+
+#[test]
+fn invalid_pricing_files_are_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pricing.json");
+    fs::write(&path, r#"{"models": [{"match": "opus"}]}"#).unwrap();
+    assert!(PricingTable::load(&path).is_err());
+}
@@ -0,0 +1,75 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use claude_monitor_core::server::{fetch_stats, subscribe_stats};
+use claude_monitor_core::stats::Stats;
+
+fn stats(active_sessions: u32) -> String {
+    let stats = Stats {
+        active_sessions,
+        ..Default::default()
+    };
+    serde_json::to_string(&stats).unwrap()
+}
+
+/// Serve one connection with `response`, returning the port and the request line
+fn serve_once(response: String) -> (u16, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request_line = String::new();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        reader.read_line(&mut request_line).unwrap();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        request_line
+    });
+    (port, handle)
+}
+
+#[test]
+fn stats_are_fetched_from_the_server() {
+    let body = stats(3);
+    let (port, server) = serve_once(format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    ));
+
+    let fetched = fetch_stats(port).unwrap();
+    assert_eq!(fetched.active_sessions, 3);
+    assert!(server.join().unwrap().starts_with("GET /api/stats "));
+}
+
+#[test]
+fn error_statuses_are_reported() {
+    let (port, _) = serve_once("HTTP/1.1 503 Service Unavailable\r\n\r\n".to_string());
+    let error = fetch_stats(port).unwrap_err();
+    assert!(error.to_string().contains("503"));
+}
+
+#[test]
+fn event_stream_delivers_every_update() {
+    let (port, server) = serve_once(format!(
+        "HTTP/1.0 200 OK\r\ncontent-type: text/event-stream\r\n\r\n\
+         event: stats\ndata: {{\"stats\":{},\"sessions\":[]}}\n\n\
+         :\n\n\
+         event: other\ndata: ignored\n\n\
+         event: stats\ndata: {{\"stats\":{},\"sessions\":[]}}\n\n",
+        stats(1),
+        stats(2)
+    ));
+
+    let mut received = Vec::new();
+    subscribe_stats(port, |stats| received.push(stats.active_sessions)).unwrap();
+
+    assert_eq!(received, vec![1, 2]);
+    assert!(server.join().unwrap().starts_with("GET /api/events "));
+}
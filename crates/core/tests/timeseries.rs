@@ -0,0 +1,105 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use claude_monitor_core::parser::TokenUsage;
+use claude_monitor_core::timeseries::{Bucket, Timeseries, MAX_BUCKETS};
+
+/// Bucket boundaries follow local time; every test uses the same zone, with
+/// DST ending on 2025-10-26 and starting on 2025-03-30
+fn berlin() {
+    std::env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+}
+
+fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+}
+
+fn tokens(input: u64) -> TokenUsage {
+    TokenUsage {
+        input_tokens: input,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn day_buckets_follow_local_midnight_across_dst() {
+    berlin();
+    // Local 2025-03-29 to 2025-04-01
+    let mut series = Timeseries::new(
+        Bucket::Day,
+        None,
+        Some(utc(2025, 3, 28, 23, 0)),
+        Some(utc(2025, 3, 31, 22, 0)),
+    );
+    series.add(utc(2025, 3, 30, 21, 30), &tokens(5), 0.0, None);
+    series.fill_gaps().unwrap();
+
+    let days: Vec<String> = series
+        .buckets
+        .iter()
+        .map(|b| Bucket::Day.label(b.start))
+        .collect();
+    assert_eq!(days, vec!["2025-03-29", "2025-03-30", "2025-03-31"]);
+    // The day clocks went forward is an hour short
+    let lengths: Vec<i64> = series
+        .buckets
+        .iter()
+        .map(|b| (b.end - b.start).num_hours())
+        .collect();
+    assert_eq!(lengths, vec![24, 23, 24]);
+    assert_eq!(series.buckets[1].usage.input_tokens, 5);
+}
+
+#[test]
+fn the_repeated_hour_is_two_buckets() {
+    berlin();
+    // Local 00:00 to 04:00 on 2025-10-26 is five hours, 02:00 happening twice
+    let mut series = Timeseries::new(
+        Bucket::Hour,
+        None,
+        Some(utc(2025, 10, 25, 22, 0)),
+        Some(utc(2025, 10, 26, 3, 0)),
+    );
+    series.add(utc(2025, 10, 26, 0, 30), &tokens(1), 0.0, None);
+    series.add(utc(2025, 10, 26, 1, 30), &tokens(2), 0.0, None);
+    series.fill_gaps().unwrap();
+
+    assert_eq!(series.buckets.len(), 5);
+    let labels: Vec<String> = series
+        .buckets
+        .iter()
+        .map(|b| Bucket::Hour.label(b.start))
+        .collect();
+    assert_eq!(labels[2], "2025-10-26 02:00");
+    assert_eq!(labels[3], "2025-10-26 02:00");
+    assert_eq!(series.buckets[2].usage.input_tokens, 1);
+    assert_eq!(series.buckets[3].usage.input_tokens, 2);
+    assert!(series
+        .buckets
+        .iter()
+        .all(|b| b.end - b.start == Duration::hours(1)));
+}
+
+#[test]
+fn ranges_are_limited_to_max_buckets() {
+    berlin();
+    let from = utc(2025, 1, 1, 0, 0);
+    let fits = from + Duration::hours(MAX_BUCKETS as i64);
+
+    let mut series = Timeseries::new(Bucket::Hour, None, Some(from), Some(fits));
+    series.fill_gaps().unwrap();
+    assert_eq!(series.buckets.len(), MAX_BUCKETS);
+
+    let mut series = Timeseries::new(
+        Bucket::Hour,
+        None,
+        Some(from),
+        Some(fits + Duration::hours(1)),
+    );
+    assert!(series.fill_gaps().is_err());
+
+    // Without an end, the range runs to the last usage
+    let mut series = Timeseries::new(Bucket::Month, None, Some(utc(1, 1, 1, 0, 0)), None);
+    series.add(from, &tokens(1), 0.0, None);
+    let error = series.fill_gaps().unwrap_err();
+    assert!(error.contains("month"));
+}
@@ -0,0 +1,487 @@
+pub mod notifier;
+pub mod webhook;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::format::{format_minutes, format_tokens};
+use crate::monitor::blocks::SessionBlock;
+use crate::monitor::events::{SessionEvent, SessionEventKind};
+use crate::monitor::state::Stats;
+use crate::parser::{BudgetInfo, SessionData};
+use notifier::{Notifier, NotifierConfig};
+
+/// How often alerts are re-checked without any file changes, so block resets
+/// and sessions going quiet are noticed
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Default budget percentages that raise an alert
+pub const DEFAULT_ALERT_THRESHOLDS: [f64; 3] = [50.0, 75.0, 90.0];
+
+/// `[alerts]` section of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Percentages of the block's token limit that raise an alert
+    #[serde(default = "default_thresholds")]
+    pub thresholds: Vec<f64>,
+    /// Raise an alert when the budget is projected to run out within this many minutes
+    pub exhaustion_minutes: Option<i64>,
+    /// Where alerts are sent
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+}
+
+fn default_thresholds() -> Vec<f64> {
+    DEFAULT_ALERT_THRESHOLDS.to_vec()
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: default_thresholds(),
+            exhaustion_minutes: None,
+            notifiers: Vec::new(),
+        }
+    }
+}
+
+impl AlertConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.thresholds.iter().any(|&t| t.is_nan() || t <= 0.0) {
+            return Err("alerts.thresholds must be positive percentages".to_string());
+        }
+        if self.exhaustion_minutes.map(|m| m <= 0).unwrap_or(false) {
+            return Err("alerts.exhaustion_minutes must be positive".to_string());
+        }
+        self.notifiers.iter().try_for_each(NotifierConfig::validate)
+    }
+}
+
+impl fmt::Display for AlertConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let thresholds: Vec<String> = self.thresholds.iter().map(|t| t.to_string()).collect();
+        write!(f, "{}%", thresholds.join(","))?;
+        if let Some(minutes) = self.exhaustion_minutes {
+            write!(f, ", exhaustion within {}m", minutes)?;
+        }
+        let notifiers: Vec<&str> = self.notifiers.iter().map(NotifierConfig::kind).collect();
+        if notifiers.is_empty() {
+            write!(f, ", no notifiers")
+        } else {
+            write!(f, ", notify {}", notifiers.join("+"))
+        }
+    }
+}
+
+/// What an alert was raised for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Usage of the current block reached a percentage of the token limit
+    Threshold { percent: f64 },
+    /// At the current burn rate the budget runs out before the block resets
+    Exhaustion { minutes: i64 },
+}
+
+/// A budget alert, as sent to notifiers
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    #[serde(flatten)]
+    pub kind: AlertKind,
+    /// Human-readable summary
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+    /// Budget of the block when the alert was raised
+    pub budget: BudgetInfo,
+    /// Start of the usage block the alert belongs to
+    pub block_start: Option<DateTime<Utc>>,
+}
+
+impl Alert {
+    fn new(kind: AlertKind, stats: &Stats, now: DateTime<Utc>) -> Self {
+        let budget = &stats.budget;
+        let resets = budget
+            .reset_minutes
+            .map(|m| format!("; resets in {}", format_minutes(m)))
+            .unwrap_or_default();
+        let message = match &kind {
+            AlertKind::Threshold { percent } => format!(
+                "Used {:.0}% of the token budget ({} of {}, crossed {}%){}",
+                budget.percentage,
+                format_tokens(budget.used),
+                format_tokens(budget.limit),
+                percent,
+                resets
+            ),
+            AlertKind::Exhaustion { minutes } => format!(
+                "Token budget runs out in {} at {}/min{}",
+                format_minutes(*minutes),
+                format_tokens(stats.burn_rate.tokens_per_minute.round() as u64),
+                resets
+            ),
+        };
+
+        Self {
+            kind,
+            message,
+            timestamp: now,
+            budget: budget.clone(),
+            block_start: stats.current_block.as_ref().map(|b| b.start),
+        }
+    }
+}
+
+/// Kinds of events sent to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A budget threshold alert; `data` is the alert
+    ThresholdCrossed,
+    /// A projected exhaustion alert; `data` is the alert
+    ExhaustionProjected,
+    /// The usage block expired; `data` has the finished block
+    WindowReset,
+    /// A main session became active; `data` is the session
+    SessionStarted,
+    /// A main session saw no activity for the active threshold; `data` is the session
+    SessionIdle,
+    /// An idle or ended main session became active again; `data` is the session
+    SessionResumed,
+    /// A main session saw no activity for half an hour; `data` is the session
+    SessionEnded,
+    /// A new agent session became active; `data` is the session
+    AgentSpawned,
+    /// An agent session saw no activity for the active threshold; `data` is the session
+    AgentFinished,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ThresholdCrossed => "threshold_crossed",
+            Self::ExhaustionProjected => "exhaustion_projected",
+            Self::WindowReset => "window_reset",
+            Self::SessionStarted => "session_started",
+            Self::SessionIdle => "session_idle",
+            Self::SessionResumed => "session_resumed",
+            Self::SessionEnded => "session_ended",
+            Self::AgentSpawned => "agent_spawned",
+            Self::AgentFinished => "agent_finished",
+        }
+    }
+}
+
+impl From<SessionEventKind> for EventKind {
+    fn from(kind: SessionEventKind) -> Self {
+        match kind {
+            SessionEventKind::SessionStarted => Self::SessionStarted,
+            SessionEventKind::SessionIdle => Self::SessionIdle,
+            SessionEventKind::SessionResumed => Self::SessionResumed,
+            SessionEventKind::SessionEnded => Self::SessionEnded,
+            SessionEventKind::AgentSpawned => Self::AgentSpawned,
+            SessionEventKind::AgentFinished => Self::AgentFinished,
+        }
+    }
+}
+
+/// An event as POSTed to webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// Unique id; retries of the same event keep it
+    pub id: String,
+    pub event: EventKind,
+    pub timestamp: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+impl Event {
+    pub fn new(event: EventKind, data: impl Serialize, timestamp: DateTime<Utc>) -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        Self {
+            id: format!(
+                "{}-{}",
+                timestamp.timestamp_millis(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ),
+            event,
+            timestamp,
+            data: serde_json::to_value(data).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&Alert> for Event {
+    fn from(alert: &Alert) -> Self {
+        let kind = match alert.kind {
+            AlertKind::Threshold { .. } => EventKind::ThresholdCrossed,
+            AlertKind::Exhaustion { .. } => EventKind::ExhaustionProjected,
+        };
+        Self::new(kind, alert, alert.timestamp)
+    }
+}
+
+/// Data of a `window_reset` event
+#[derive(Debug, Serialize)]
+struct WindowReset<'a> {
+    /// The block that expired
+    previous_block: &'a SessionBlock,
+    /// Start of the block that replaced it, if usage continued
+    block_start: Option<DateTime<Utc>>,
+}
+
+/// Alerts already raised in the current usage block. Each threshold and the
+/// exhaustion warning fire at most once per block and re-arm when it resets.
+#[derive(Debug, Default)]
+pub struct AlertState {
+    block_start: Option<DateTime<Utc>>,
+    /// Highest threshold alerted on
+    fired_percent: Option<f64>,
+    exhaustion_fired: bool,
+}
+
+impl AlertState {
+    /// Alerts newly raised by `stats`
+    pub fn evaluate(
+        &mut self,
+        stats: &Stats,
+        config: &AlertConfig,
+        now: DateTime<Utc>,
+    ) -> Vec<Alert> {
+        let block_start = stats.current_block.as_ref().map(|b| b.start);
+        if block_start != self.block_start {
+            *self = Self {
+                block_start,
+                ..Self::default()
+            };
+        }
+        if block_start.is_none() {
+            return Vec::new();
+        }
+
+        let mut alerts = Vec::new();
+
+        // Only the highest threshold crossed since the last check is reported
+        let crossed = config
+            .thresholds
+            .iter()
+            .copied()
+            .filter(|&t| stats.budget.percentage >= t)
+            .filter(|&t| self.fired_percent.map(|fired| t > fired).unwrap_or(true))
+            .max_by(f64::total_cmp);
+        if let Some(percent) = crossed {
+            self.fired_percent = Some(percent);
+            alerts.push(Alert::new(AlertKind::Threshold { percent }, stats, now));
+        }
+
+        let exhausting = stats
+            .burn_rate
+            .minutes_to_exhaustion
+            .zip(config.exhaustion_minutes)
+            .filter(|(minutes, limit)| minutes <= limit);
+        if let Some((minutes, _)) = exhausting {
+            if !self.exhaustion_fired {
+                self.exhaustion_fired = true;
+                alerts.push(Alert::new(AlertKind::Exhaustion { minutes }, stats, now));
+            }
+        }
+
+        alerts
+    }
+}
+
+/// Checks the budget and sessions after each refresh. Alerts go to every
+/// notifier; session and block events only to webhooks.
+#[derive(Debug)]
+pub struct Alerter {
+    config: AlertConfig,
+    state: AlertState,
+    /// Block seen at the last check, to report it when it expires
+    block: Option<SessionBlock>,
+    notifiers: Arc<Vec<Notifier>>,
+}
+
+impl Alerter {
+    pub fn new(config: &Config) -> Self {
+        // Undelivered webhook events are kept next to the usage index
+        let queue_dir = config.index_file.parent();
+
+        Self {
+            config: config.alerts.clone(),
+            state: AlertState::default(),
+            block: None,
+            notifiers: Arc::new(
+                config
+                    .alerts
+                    .notifiers
+                    .iter()
+                    .map(|n| Notifier::new(n, queue_dir))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Raise alerts and events for `stats`, notifying in the background
+    pub fn check(&mut self, stats: &Stats) {
+        let now = Utc::now();
+
+        let block_start = stats.current_block.as_ref().map(|b| b.start);
+        if let Some(previous) = &self.block {
+            if Some(previous.start) != block_start {
+                tracing::info!("Usage block from {} has reset", previous.start);
+                let reset = WindowReset {
+                    previous_block: previous,
+                    block_start,
+                };
+                self.emit(&Event::new(EventKind::WindowReset, reset, now));
+            }
+        }
+        self.block = stats.current_block.clone();
+
+        for alert in self.state.evaluate(stats, &self.config, now) {
+            tracing::warn!("Budget alert: {}", alert.message);
+
+            let notifiers = self.notifiers.clone();
+            tokio::spawn(async move {
+                for notifier in notifiers.iter() {
+                    if let Err(e) = notifier.send(&alert).await {
+                        tracing::warn!("Failed to send alert via {}: {}", notifier.kind(), e);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Send session lifecycle `events` to webhooks with the sessions they're about
+    pub fn forward(&self, events: &[SessionEvent], sessions: &HashMap<String, SessionData>) {
+        for event in events {
+            if let Some(session) = sessions.get(&event.session_key) {
+                self.emit(&Event::new(event.kind.into(), session, event.timestamp));
+            }
+        }
+    }
+
+    /// Send an event to the webhooks
+    fn emit(&self, event: &Event) {
+        for notifier in self.notifiers.iter() {
+            if let Notifier::Webhook(webhook) = notifier {
+                webhook.send(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::blocks::compute_blocks;
+    use crate::parser::TimestampedUsage;
+    use crate::pricing::PricingTable;
+    use chrono::Duration;
+
+    /// Stats of a block started at `start` with `percent` of the budget used
+    fn stats(start: DateTime<Utc>, percent: u64, minutes_to_exhaustion: Option<i64>) -> Stats {
+        let usage = TimestampedUsage {
+            timestamp: start,
+            usage: crate::parser::TokenUsage {
+                input_tokens: percent,
+                ..Default::default()
+            },
+            model: "claude-sonnet-4-5".to_string(),
+            message_key: "m".to_string(),
+            git_branch: None,
+        };
+        let block = compute_blocks([&usage], 5, &PricingTable::default(), start)
+            .pop()
+            .unwrap();
+        let mut stats = Stats {
+            budget: BudgetInfo::new(percent, 100, Some(block.end), 5),
+            current_block: Some(block),
+            ..Default::default()
+        };
+        stats.burn_rate.minutes_to_exhaustion = minutes_to_exhaustion;
+        stats
+    }
+
+    fn kinds(alerts: Vec<Alert>) -> Vec<AlertKind> {
+        alerts.into_iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn thresholds_fire_once_per_block() {
+        let config = AlertConfig::default();
+        let mut state = AlertState::default();
+        let block = Utc::now();
+        let now = Utc::now();
+
+        assert!(state
+            .evaluate(&stats(block, 40, None), &config, now)
+            .is_empty());
+        assert_eq!(
+            kinds(state.evaluate(&stats(block, 60, None), &config, now)),
+            vec![AlertKind::Threshold { percent: 50.0 }]
+        );
+        assert!(state
+            .evaluate(&stats(block, 70, None), &config, now)
+            .is_empty());
+        // Jumping past two thresholds reports the higher one only
+        assert_eq!(
+            kinds(state.evaluate(&stats(block, 95, None), &config, now)),
+            vec![AlertKind::Threshold { percent: 90.0 }]
+        );
+        assert!(state
+            .evaluate(&stats(block, 99, None), &config, now)
+            .is_empty());
+
+        // A new block re-arms every threshold
+        let next = block + Duration::hours(5);
+        assert_eq!(
+            kinds(state.evaluate(&stats(next, 55, None), &config, now)),
+            vec![AlertKind::Threshold { percent: 50.0 }]
+        );
+    }
+
+    #[test]
+    fn exhaustion_fires_once_within_the_horizon() {
+        let config = AlertConfig {
+            exhaustion_minutes: Some(30),
+            ..Default::default()
+        };
+        let mut state = AlertState::default();
+        let block = Utc::now();
+        let now = Utc::now();
+
+        assert!(state
+            .evaluate(&stats(block, 10, Some(45)), &config, now)
+            .is_empty());
+        assert_eq!(
+            kinds(state.evaluate(&stats(block, 20, Some(25)), &config, now)),
+            vec![AlertKind::Exhaustion { minutes: 25 }]
+        );
+        assert!(state
+            .evaluate(&stats(block, 30, Some(10)), &config, now)
+            .is_empty());
+    }
+
+    #[test]
+    fn session_events_keep_their_names() {
+        use SessionEventKind::*;
+        for kind in [
+            SessionStarted,
+            SessionIdle,
+            SessionResumed,
+            SessionEnded,
+            AgentSpawned,
+            AgentFinished,
+        ] {
+            let name = serde_json::to_value(kind).unwrap();
+            assert_eq!(name, EventKind::from(kind).as_str());
+        }
+    }
+}
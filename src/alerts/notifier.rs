@@ -0,0 +1,155 @@
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::webhook::{self, Backoff, Webhook, WebhookHandle};
+use super::{Alert, Event, EventKind};
+
+/// How long a notifier may take before it's abandoned
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A `[[alerts.notifiers]]` entry in the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum NotifierConfig {
+    /// Desktop notification (`osascript` on macOS, `notify-send` elsewhere)
+    Desktop,
+    /// Shell command run with the alert in `CLAUDE_MONITOR_ALERT*` variables
+    Command { command: String },
+    /// HTTP endpoint that alerts and session events are POSTed to as JSON
+    Webhook {
+        url: String,
+        /// Key for the HMAC-SHA256 signature header
+        secret: Option<String>,
+        /// Events to send; all of them if empty
+        #[serde(default)]
+        events: Vec<EventKind>,
+    },
+}
+
+impl NotifierConfig {
+    /// Short name for logs and `config` output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::Command { .. } => "command",
+            Self::Webhook { .. } => "webhook",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Desktop => Ok(()),
+            Self::Command { command } if command.trim().is_empty() => {
+                Err("alerts command notifier needs a command".to_string())
+            }
+            Self::Command { .. } => Ok(()),
+            Self::Webhook { url, .. } => reqwest::Url::parse(url)
+                .map(|_| ())
+                .map_err(|e| format!("invalid alerts webhook url \"{}\": {}", url, e)),
+        }
+    }
+}
+
+/// A configured alert destination
+#[derive(Debug)]
+pub enum Notifier {
+    Desktop,
+    Command(String),
+    Webhook(WebhookHandle),
+}
+
+impl Notifier {
+    /// Create a notifier; webhooks start delivering in the background and
+    /// queue undelivered events in `queue_dir`
+    pub fn new(config: &NotifierConfig, queue_dir: Option<&Path>) -> Self {
+        match config {
+            NotifierConfig::Desktop => Self::Desktop,
+            NotifierConfig::Command { command } => Self::Command(command.clone()),
+            NotifierConfig::Webhook {
+                url,
+                secret,
+                events,
+            } => {
+                let queue_file =
+                    queue_dir.map(|dir| webhook::queue_file(dir, url, secret.as_deref(), events));
+                let webhook = Webhook::new(url, secret.as_deref(), queue_file, Backoff::default());
+                Self::Webhook(webhook.spawn(events.clone()))
+            }
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::Command(_) => "command",
+            Self::Webhook(_) => "webhook",
+        }
+    }
+
+    /// Deliver one alert
+    pub async fn send(
+        &self,
+        alert: &Alert,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            Self::Desktop => {
+                let mut command = desktop_command(&alert.message);
+                run(&mut command).await
+            }
+            Self::Command(shell) => {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg(shell)
+                    .env("CLAUDE_MONITOR_ALERT", serde_json::to_string(alert)?)
+                    .env("CLAUDE_MONITOR_ALERT_MESSAGE", &alert.message)
+                    .env(
+                        "CLAUDE_MONITOR_ALERT_PERCENT",
+                        alert.budget.percentage.to_string(),
+                    );
+                run(&mut command).await
+            }
+            // Delivered, and retried if need be, by the webhook's own task
+            Self::Webhook(webhook) => {
+                webhook.send(&Event::from(alert));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn desktop_command(message: &str) -> Command {
+    // AppleScript string literals escape backslashes and double quotes
+    let quoted = message.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification \"{}\" with title \"Claude Monitor\"",
+        quoted
+    ));
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn desktop_command(message: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.arg("Claude Monitor").arg(message);
+    command
+}
+
+/// Run a notifier process to completion, failing on a non-zero exit
+async fn run(command: &mut Command) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    command
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(NOTIFY_TIMEOUT, command.output())
+        .await
+        .map_err(|_| "timed out")??;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", output.status, stderr.trim()).into());
+    }
+    Ok(())
+}
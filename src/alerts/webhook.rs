@@ -0,0 +1,516 @@
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::{Event, EventKind};
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>` when a secret is configured
+pub const SIGNATURE_HEADER: &str = "X-Claude-Monitor-Signature";
+/// Header carrying the event kind, e.g. `session_started`
+pub const EVENT_HEADER: &str = "X-Claude-Monitor-Event";
+/// Header carrying the event id; retries of one event share it
+pub const DELIVERY_HEADER: &str = "X-Claude-Monitor-Delivery";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Undelivered events kept per webhook; the oldest are dropped beyond this
+const MAX_QUEUED: usize = 1000;
+
+/// Delay before retrying a failed delivery, doubling with every attempt
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(2),
+            max: Duration::from_secs(600),
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay after the `attempt`th consecutive failure (starting at 1)
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+/// Queue file in `dir` of the webhook posting `events` (all if empty) to `url`,
+/// signed with `secret`; webhooks differing in any of them get their own file
+pub fn queue_file(dir: &Path, url: &str, secret: Option<&str>, events: &[EventKind]) -> PathBuf {
+    let mut kinds: Vec<&str> = events.iter().map(EventKind::as_str).collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    hasher.update(b"\n");
+    if let Some(secret) = secret {
+        hasher.update(b"secret:");
+        hasher.update(secret.as_bytes());
+    }
+    hasher.update(b"\n");
+    hasher.update(kinds.join(",").as_bytes());
+    let digest = hex::encode(hasher.finalize());
+    dir.join(format!("webhook-{}.jsonl", &digest[..16]))
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Events not delivered yet, mirrored to a JSON-lines file so they survive restarts
+#[derive(Debug)]
+struct Queue {
+    path: Option<PathBuf>,
+    events: VecDeque<Event>,
+}
+
+impl Queue {
+    /// Load the events left over from a previous run
+    fn open(path: Option<PathBuf>) -> Self {
+        let events = path
+            .as_deref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { path, events }
+    }
+
+    fn front(&self) -> Option<&Event> {
+        self.events.front()
+    }
+
+    async fn push(&mut self, event: Event) {
+        if self.events.len() >= MAX_QUEUED {
+            if let Some(dropped) = self.events.pop_front() {
+                tracing::warn!("Webhook queue full, dropping event {}", dropped.id);
+            }
+        }
+        self.events.push_back(event);
+        self.save().await;
+    }
+
+    async fn pop(&mut self) {
+        self.events.pop_front();
+        self.save().await;
+    }
+
+    /// Rewrite the queue file on the blocking thread pool; writing a temporary
+    /// file first keeps it intact on a crash
+    async fn save(&self) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+
+        let mut content = String::new();
+        for event in &self.events {
+            if let Ok(line) = serde_json::to_string(event) {
+                content.push_str(&line);
+                content.push('\n');
+            }
+        }
+
+        let result = tokio::task::spawn_blocking(move || {
+            let tmp = path.with_extension("tmp");
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&tmp, content))
+                .and_then(|_| fs::rename(&tmp, &path))
+                .map_err(|e| format!("Failed to write webhook queue {:?}: {}", path, e))
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!("{}", e),
+            Err(e) => tracing::warn!("Failed to write webhook queue: {}", e),
+        }
+    }
+}
+
+/// Why a delivery failed
+#[derive(Debug)]
+enum Failure {
+    /// Worth retrying: connection errors, timeouts, 408, 429 and 5xx
+    Transient(String),
+    /// The endpoint rejected the event; retrying won't help
+    Permanent(String),
+}
+
+/// Posts events to one URL, retrying with backoff and queueing on disk
+#[derive(Debug)]
+pub struct Webhook {
+    url: String,
+    secret: Option<String>,
+    client: reqwest::Client,
+    backoff: Backoff,
+    queue: Queue,
+}
+
+impl Webhook {
+    /// A webhook for `url`; events still in `queue_file` are delivered first
+    pub fn new(
+        url: &str,
+        secret: Option<&str>,
+        queue_file: Option<PathBuf>,
+        backoff: Backoff,
+    ) -> Self {
+        Self {
+            url: url.to_string(),
+            secret: secret.map(str::to_string),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            backoff,
+            queue: Queue::open(queue_file),
+        }
+    }
+
+    /// Deliver in the background. Only `events` kinds are sent, or all if it's empty.
+    pub fn spawn(self, events: Vec<EventKind>) -> WebhookHandle {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(self.run(rx));
+        WebhookHandle { events, tx }
+    }
+
+    /// Deliver queued events oldest first, one at a time so they arrive in order
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<Event>) {
+        let mut attempt = 0;
+        loop {
+            // Persist everything that arrived meanwhile before trying the network
+            while let Ok(event) = rx.try_recv() {
+                self.queue.push(event).await;
+            }
+
+            let Some(event) = self.queue.front().cloned() else {
+                match rx.recv().await {
+                    Some(event) => self.queue.push(event).await,
+                    None => return,
+                }
+                continue;
+            };
+
+            match self.deliver(&event).await {
+                Ok(()) => {
+                    attempt = 0;
+                    self.queue.pop().await;
+                }
+                Err(Failure::Permanent(e)) => {
+                    tracing::warn!("Webhook {} rejected event {}: {}", self.url, event.id, e);
+                    attempt = 0;
+                    self.queue.pop().await;
+                }
+                Err(Failure::Transient(e)) => {
+                    attempt += 1;
+                    let delay = self.backoff.delay(attempt);
+                    tracing::warn!(
+                        "Webhook {} failed (attempt {}), retrying in {}s: {}",
+                        self.url,
+                        attempt,
+                        delay.as_secs(),
+                        e
+                    );
+
+                    // Keep queueing new events while waiting
+                    let sleep = tokio::time::sleep(delay);
+                    tokio::pin!(sleep);
+                    loop {
+                        tokio::select! {
+                            _ = &mut sleep => break,
+                            Some(event) = rx.recv() => self.queue.push(event).await,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, event: &Event) -> Result<(), Failure> {
+        let body = serde_json::to_vec(event).map_err(|e| Failure::Permanent(e.to_string()))?;
+
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.event.as_str())
+            .header(DELIVERY_HEADER, &event.id);
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+        }
+
+        let status = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| Failure::Transient(e.to_string()))?
+            .status();
+
+        if status.is_success() {
+            Ok(())
+        } else if status.is_client_error()
+            && status != StatusCode::REQUEST_TIMEOUT
+            && status != StatusCode::TOO_MANY_REQUESTS
+        {
+            Err(Failure::Permanent(status.to_string()))
+        } else {
+            Err(Failure::Transient(status.to_string()))
+        }
+    }
+}
+
+/// Sends events to a running [`Webhook`]
+#[derive(Debug, Clone)]
+pub struct WebhookHandle {
+    events: Vec<EventKind>,
+    tx: mpsc::UnboundedSender<Event>,
+}
+
+impl WebhookHandle {
+    /// Queue an event for delivery unless this webhook doesn't take its kind
+    pub fn send(&self, event: &Event) {
+        if self.events.is_empty() || self.events.contains(&event.event) {
+            let _ = self.tx.send(event.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A request received by the stand-in endpoint
+    #[derive(Debug)]
+    struct Request {
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    }
+
+    impl Request {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        }
+
+        fn event(&self) -> Event {
+            serde_json::from_slice(&self.body).unwrap()
+        }
+    }
+
+    /// Local HTTP endpoint answering requests with `statuses` in turn, then 200
+    async fn stand_in(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let head_end = loop {
+                    let mut chunk = [0u8; 4096];
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break i + 4;
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+                let headers: Vec<(String, String)> = head
+                    .lines()
+                    .skip(1)
+                    .filter_map(|l| l.split_once(':'))
+                    .map(|(n, v)| (n.trim().to_string(), v.trim().to_string()))
+                    .collect();
+                let length: usize = headers
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case("content-length"))
+                    .map(|(_, v)| v.parse().unwrap())
+                    .unwrap_or(0);
+                while buf.len() < head_end + length {
+                    let mut chunk = [0u8; 4096];
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+
+                let status = statuses.next().unwrap_or(200);
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                let body = buf[head_end..head_end + length].to_vec();
+                let _ = tx.send(Request { headers, body });
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn event(kind: EventKind) -> Event {
+        Event::new(kind, serde_json::json!({ "session_id": "abc" }), Utc::now())
+    }
+
+    fn fast_backoff() -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(10),
+            max: Duration::from_millis(40),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let backoff = Backoff::default();
+        assert_eq!(backoff.delay(1), Duration::from_secs(2));
+        assert_eq!(backoff.delay(2), Duration::from_secs(4));
+        assert_eq!(backoff.delay(5), Duration::from_secs(32));
+        assert_eq!(backoff.delay(40), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn queue_files_are_kept_per_webhook() {
+        let dir = Path::new("/queues");
+        let url = "http://example.invalid/hook";
+        let all = queue_file(dir, url, None, &[]);
+        let started = [EventKind::SessionStarted];
+        let both = [EventKind::SessionStarted, EventKind::SessionEnded];
+        let reversed = [EventKind::SessionEnded, EventKind::SessionStarted];
+
+        assert_eq!(all, queue_file(dir, url, None, &[]));
+        assert_ne!(
+            all,
+            queue_file(dir, "http://example.invalid/other", None, &[])
+        );
+        assert_ne!(all, queue_file(dir, url, Some("s3cret"), &[]));
+        assert_ne!(all, queue_file(dir, url, None, &started));
+        assert_ne!(
+            queue_file(dir, url, None, &started),
+            queue_file(dir, url, None, &both)
+        );
+        assert_eq!(
+            queue_file(dir, url, None, &both),
+            queue_file(dir, url, None, &reversed)
+        );
+    }
+
+    #[tokio::test]
+    async fn events_are_signed() {
+        let (url, mut requests) = stand_in(vec![]).await;
+        let handle = Webhook::new(&url, Some("s3cret"), None, fast_backoff()).spawn(vec![]);
+
+        let sent = event(EventKind::SessionStarted);
+        handle.send(&sent);
+
+        let request = requests.recv().await.unwrap();
+        let expected = format!("sha256={}", sign("s3cret", &request.body));
+        assert_eq!(request.header(SIGNATURE_HEADER), Some(expected.as_str()));
+        assert_eq!(request.header(EVENT_HEADER), Some("session_started"));
+        assert_eq!(request.header(DELIVERY_HEADER), Some(sent.id.as_str()));
+        assert_eq!(request.event().data["session_id"], "abc");
+    }
+
+    #[tokio::test]
+    async fn filtered_kinds_are_not_sent() {
+        let (url, mut requests) = stand_in(vec![]).await;
+        let handle =
+            Webhook::new(&url, None, None, fast_backoff()).spawn(vec![EventKind::AgentSpawned]);
+
+        handle.send(&event(EventKind::SessionStarted));
+        handle.send(&event(EventKind::AgentSpawned));
+
+        let request = requests.recv().await.unwrap();
+        assert_eq!(request.event().event, EventKind::AgentSpawned);
+        assert_eq!(request.header(SIGNATURE_HEADER), None);
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried() {
+        let (url, mut requests) = stand_in(vec![500, 429, 200]).await;
+        let handle = Webhook::new(&url, None, None, fast_backoff()).spawn(vec![]);
+
+        let sent = event(EventKind::ThresholdCrossed);
+        handle.send(&sent);
+
+        for _ in 0..3 {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(request.header(DELIVERY_HEADER), Some(sent.id.as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_events_are_dropped() {
+        let (url, mut requests) = stand_in(vec![400]).await;
+        let handle = Webhook::new(&url, None, None, fast_backoff()).spawn(vec![]);
+
+        let rejected = event(EventKind::SessionStarted);
+        let next = event(EventKind::SessionEnded);
+        handle.send(&rejected);
+        handle.send(&next);
+
+        assert_eq!(requests.recv().await.unwrap().event().id, rejected.id);
+        assert_eq!(requests.recv().await.unwrap().event().id, next.id);
+    }
+
+    #[tokio::test]
+    async fn undelivered_events_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = queue_file(dir.path(), "http://example.invalid/hook", None, &[]);
+
+        // Nothing listens on this port, so the event stays queued
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let down = format!("http://{}/hook", closed.local_addr().unwrap());
+        drop(closed);
+        let handle = Webhook::new(&down, None, Some(file.clone()), fast_backoff()).spawn(vec![]);
+        let sent = event(EventKind::WindowReset);
+        handle.send(&sent);
+
+        let queued = || Queue::open(Some(file.clone())).events.len();
+        for _ in 0..100 {
+            if queued() == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(queued(), 1);
+
+        // The next run delivers it without being sent anything
+        let (url, mut requests) = stand_in(vec![]).await;
+        let _restarted = Webhook::new(&url, None, Some(file.clone()), fast_backoff()).spawn(vec![]);
+        assert_eq!(requests.recv().await.unwrap().event().id, sent.id);
+
+        for _ in 0..100 {
+            if queued() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(queued(), 0);
+    }
+}
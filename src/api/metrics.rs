@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::monitor::AppState;
+use crate::parser::TokenUsage;
+
+/// Accessor for one kind of token in a usage record
+type TokenField = fn(&TokenUsage) -> u64;
+
+/// Root, project, model and whether the session is an agent
+type UsageLabels<'a> = (&'a str, &'a str, &'a str, bool);
+
+/// Token counters exported per usage label set
+const TOKEN_COUNTERS: [(&str, &str, TokenField); 4] = [
+    ("input_tokens", "Input tokens used", |u| u.input_tokens),
+    ("output_tokens", "Output tokens generated", |u| u.output_tokens),
+    (
+        "cache_creation_input_tokens",
+        "Tokens written to the prompt cache",
+        |u| u.cache_creation_input_tokens,
+    ),
+    (
+        "cache_read_input_tokens",
+        "Tokens read from the prompt cache",
+        |u| u.cache_read_input_tokens,
+    ),
+];
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render(state: &AppState) -> String {
+    let stats = state.get_stats();
+    let mut out = String::new();
+
+    // Lifetime usage per label set, summed over sessions
+    let mut usage: BTreeMap<UsageLabels, (TokenUsage, f64)> = BTreeMap::new();
+    for session in state.sessions.values() {
+        for (model, model_usage) in &session.models {
+            let entry = usage
+                .entry((
+                    session.root.as_str(),
+                    session.project_path.as_str(),
+                    model.as_str(),
+                    session.is_agent,
+                ))
+                .or_default();
+            entry.0 += model_usage.clone();
+            entry.1 += state.pricing.cost(model, model_usage);
+        }
+    }
+
+    for (name, help, value) in TOKEN_COUNTERS {
+        header(&mut out, &format!("{}_total", name), help, "counter");
+        for (key, (tokens, _)) in &usage {
+            sample(
+                &mut out,
+                &format!("{}_total", name),
+                &usage_labels(key),
+                value(tokens) as f64,
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "cost_usd_total",
+        "Estimated API-equivalent cost in USD",
+        "counter",
+    );
+    for (key, (_, cost)) in &usage {
+        sample(&mut out, "cost_usd_total", &usage_labels(key), *cost);
+    }
+
+    let gauges: [(&str, &str, f64); 7] = [
+        (
+            "budget_used_tokens",
+            "Billable tokens used in the current usage block",
+            stats.budget.used as f64,
+        ),
+        (
+            "budget_limit_tokens",
+            "Token limit per usage block",
+            stats.budget.limit as f64,
+        ),
+        (
+            "budget_used_percent",
+            "Share of the block's token limit used",
+            stats.budget.percentage,
+        ),
+        (
+            "budget_reset_seconds",
+            "Seconds until the current usage block resets",
+            stats.budget.reset_minutes.unwrap_or(0) as f64 * 60.0,
+        ),
+        (
+            "burn_rate_tokens_per_minute",
+            "Recent billable tokens per minute",
+            stats.burn_rate.tokens_per_minute,
+        ),
+        (
+            "active_sessions",
+            "Sessions active within the activity threshold",
+            stats.active_sessions as f64,
+        ),
+        (
+            "active_agents",
+            "Agents active within the activity threshold",
+            stats.active_agents as f64,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        header(&mut out, name, help, "gauge");
+        sample(&mut out, name, &[], value);
+    }
+
+    header(&mut out, "sessions", "Sessions loaded", "gauge");
+    sample(&mut out, "sessions", &[], state.sessions.len() as f64);
+
+    let metrics = &state.metrics;
+    let refresh_counters: [(&str, &str, f64); 4] = [
+        (
+            "refreshes_total",
+            "Refreshes that succeeded",
+            metrics.refreshes as f64,
+        ),
+        (
+            "refresh_errors_total",
+            "Refreshes that failed",
+            metrics.refresh_errors as f64,
+        ),
+        (
+            "refresh_duration_seconds_total",
+            "Time spent refreshing",
+            metrics.refresh_seconds_total,
+        ),
+        (
+            "parse_errors_total",
+            "Session files that couldn't be read or parsed",
+            metrics.parse_errors as f64,
+        ),
+    ];
+    for (name, help, value) in refresh_counters {
+        header(&mut out, name, help, "counter");
+        sample(&mut out, name, &[], value);
+    }
+
+    header(
+        &mut out,
+        "last_refresh_duration_seconds",
+        "Duration of the most recent refresh",
+        "gauge",
+    );
+    sample(
+        &mut out,
+        "last_refresh_duration_seconds",
+        &[],
+        metrics.last_refresh_seconds,
+    );
+
+    out
+}
+
+/// Label pairs of a usage label set
+fn usage_labels<'a>(
+    &(root, project, model, agent): &UsageLabels<'a>,
+) -> [(&'static str, &'a str); 4] {
+    [
+        ("root", root),
+        ("project", project),
+        ("model", model),
+        ("agent", if agent { "true" } else { "false" }),
+    ]
+}
+
+/// Write the HELP and TYPE lines of a metric
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP claude_monitor_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE claude_monitor_{} {}", name, kind);
+}
+
+/// Write one sample line
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let _ = write!(out, "claude_monitor_{}", name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", format_value(value));
+}
+
+/// Format a sample value; Rust prints infinities as "inf" and NaN as "NaN",
+/// Prometheus expects "+Inf", "-Inf" and "NaN"
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser::SessionData;
+    use std::path::Path;
+
+    #[test]
+    fn values_use_prometheus_spelling() {
+        assert_eq!(format_value(1.5), "1.5");
+        assert_eq!(format_value(3.0), "3");
+        assert_eq!(format_value(f64::INFINITY), "+Inf");
+        assert_eq!(format_value(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(format_value(f64::NAN), "NaN");
+    }
+
+    #[test]
+    fn exposition_lists_usage_per_label_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            index_file: dir.path().join("index.db"),
+            pricing_file: dir.path().join("pricing.json"),
+            ..Config::default()
+        };
+        let mut state = AppState::read_only(&config);
+        let mut session = SessionData::from_path(Path::new("/p/a.jsonl"), "work");
+        session.project_path = "/src/\"quoted\"".to_string();
+        session.is_agent = true;
+        session.models.insert(
+            "claude-sonnet-4-5".to_string(),
+            TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 10,
+                ..Default::default()
+            },
+        );
+        state.sessions.insert(session.key(), session);
+        state.metrics.refreshes = 3;
+        state.metrics.refresh_errors = 1;
+
+        let text = render(&state);
+        let lines: Vec<&str> = text.lines().collect();
+        let labels =
+            r#"{root="work",project="/src/\"quoted\"",model="claude-sonnet-4-5",agent="true"}"#;
+        let line = |name: &str, value: &str| format!("claude_monitor_{}{} {}", name, labels, value);
+
+        assert!(lines.contains(&"# TYPE claude_monitor_input_tokens_total counter"));
+        assert!(lines.contains(&line("input_tokens_total", "1000000").as_str()));
+        assert!(lines.contains(&line("cost_usd_total", "3.00015").as_str()));
+        assert!(lines.contains(&"claude_monitor_sessions 1"));
+        assert!(lines.contains(&"claude_monitor_refreshes_total 3"));
+        assert!(lines.contains(&"claude_monitor_refresh_errors_total 1"));
+        // Every sample follows its metric's HELP and TYPE lines
+        for line in lines.iter().filter(|l| !l.starts_with('#')) {
+            let name = line.split(['{', ' ']).next().unwrap();
+            assert!(text.contains(&format!("# TYPE {} ", name)), "{}", line);
+        }
+    }
+}
@@ -1,32 +1,93 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::{Html, IntoResponse},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse,
+    },
     routing::get,
     Json, Router,
 };
+use futures::{stream::Stream, StreamExt};
+use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_stream::wrappers::BroadcastStream;
 use tower_http::services::ServeDir;
 
-use crate::monitor::{state::Stats, AppState};
+use crate::monitor::{
+    blocks::SessionBlock,
+    detail::{ProjectDetail, SessionDetail},
+    events::SessionEvent,
+    history::{HistoryPage, HistoryQuery},
+    state::Stats,
+    timeseries::{self, Bucket, GroupBy, Timeseries},
+    tools::ToolSummary,
+    AppState,
+};
 use crate::web::templates;
 
 type SharedState = Arc<RwLock<AppState>>;
 
+/// Prompts returned per history page unless `limit` is given
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Most prompts returned per history page
+const MAX_HISTORY_LIMIT: usize = 1000;
+
+/// Session events returned unless `limit` is given
+const DEFAULT_EVENTS_LIMIT: usize = 100;
+
+/// Query parameters limiting results to one data root
+#[derive(Debug, Deserialize)]
+struct RootFilter {
+    root: Option<String>,
+}
+
+impl RootFilter {
+    /// The requested root label, or an error if no such root is configured
+    fn label<'a>(&'a self, state: &AppState) -> Result<Option<&'a str>, (StatusCode, String)> {
+        match self.root.as_deref() {
+            Some(label) if !state.config.roots.iter().any(|r| r.label == label) => Err((
+                StatusCode::NOT_FOUND,
+                format!("Unknown data root: {}", label),
+            )),
+            label => Ok(label),
+        }
+    }
+}
+
 /// Create the main router
 pub fn create_router(state: SharedState) -> Router {
     Router::new()
         // Main page
         .route("/", get(index_handler))
+        .route("/sessions/:project/:id", get(session_page_handler))
+        .route("/projects/:path", get(project_page_handler))
+        .route("/history", get(history_page_handler))
         // API routes
         .route("/api/stats", get(stats_handler))
         .route("/api/sessions", get(sessions_handler))
+        .route("/api/sessions/:project/:id", get(session_detail_handler))
+        .route("/api/status", get(status_handler))
         .route("/api/refresh", get(refresh_handler))
+        .route("/api/events", get(events_handler))
+        .route("/api/events/recent", get(recent_events_handler))
+        .route("/api/projects/:path", get(project_detail_handler))
+        .route("/api/blocks", get(blocks_handler))
+        .route("/api/timeseries", get(timeseries_handler))
+        .route("/api/tools", get(tools_handler))
+        .route("/api/history", get(history_handler))
+        // Prometheus metrics
+        .route("/metrics", get(metrics_handler))
         // HTMX partials
         .route("/partials/budget", get(budget_partial_handler))
         .route("/partials/stats", get(stats_partial_handler))
         .route("/partials/sessions", get(sessions_partial_handler))
+        .route("/partials/models", get(models_partial_handler))
+        .route("/partials/blocks", get(blocks_partial_handler))
+        .route("/partials/tools", get(tools_partial_handler))
+        .route("/partials/history", get(history_partial_handler))
         // Static files
         .nest_service("/static", ServeDir::new("static"))
         .with_state(state)
@@ -36,32 +97,311 @@ pub fn create_router(state: SharedState) -> Router {
 async fn index_handler(State(state): State<SharedState>) -> impl IntoResponse {
     let state = state.read().await;
     let stats = state.get_stats();
-    let active_sessions = state.get_active_sessions();
+    let active_sessions = state.get_active_sessions(None);
+    let blocks = state.get_recent_blocks(templates::TIMELINE_BLOCKS);
+    let tools = state.get_tools(None, None);
 
-    let html = templates::render_index(&stats, &active_sessions);
+    let html = templates::render_index(&stats, &active_sessions, &blocks, &tools);
     Html(html)
 }
 
-/// API: Get current stats
-async fn stats_handler(State(state): State<SharedState>) -> Json<Stats> {
+/// API: Get current stats, optionally for one data root (`?root=work`)
+async fn stats_handler(
+    State(state): State<SharedState>,
+    Query(filter): Query<RootFilter>,
+) -> Result<Json<Stats>, (StatusCode, String)> {
     let state = state.read().await;
-    Json(state.get_stats())
+    let root = filter.label(&state)?;
+    Ok(Json(state.get_stats_for(root)))
 }
 
-/// API: Get active sessions
+/// API: Get active sessions, optionally for one data root (`?root=work`)
 async fn sessions_handler(
     State(state): State<SharedState>,
-) -> Json<Vec<crate::parser::SessionData>> {
+    Query(filter): Query<RootFilter>,
+) -> Result<Json<Vec<crate::parser::SessionData>>, (StatusCode, String)> {
+    let state = state.read().await;
+    let root = filter.label(&state)?;
+    let sessions: Vec<_> = state.get_active_sessions(root).into_iter().cloned().collect();
+    Ok(Json(sessions))
+}
+
+/// Look up a session by URL-encoded project path and session id
+fn find_session_detail(
+    state: &AppState,
+    project: &str,
+    id: &str,
+    filter: &RootFilter,
+) -> Result<SessionDetail, (StatusCode, String)> {
+    let root = filter.label(state)?;
+    state
+        .get_session_detail(project, id, root)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown session: {}", id)))
+}
+
+/// API: Per-message timeline of one session (`/api/sessions/%2FUsers%2Fme%2Fapp/<id>`)
+async fn session_detail_handler(
+    State(state): State<SharedState>,
+    Path((project, id)): Path<(String, String)>,
+    Query(filter): Query<RootFilter>,
+) -> Result<Json<SessionDetail>, (StatusCode, String)> {
+    let state = state.read().await;
+    find_session_detail(&state, &project, &id, &filter).map(Json)
+}
+
+/// Session drill-down page
+async fn session_page_handler(
+    State(state): State<SharedState>,
+    Path((project, id)): Path<(String, String)>,
+    Query(filter): Query<RootFilter>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let state = state.read().await;
+    let detail = find_session_detail(&state, &project, &id, &filter)?;
+    Ok(Html(templates::render_session_page(&detail)))
+}
+
+/// Look up a project by URL-encoded path
+fn find_project_detail(
+    state: &AppState,
+    path: &str,
+    filter: &RootFilter,
+) -> Result<ProjectDetail, (StatusCode, String)> {
+    let root = filter.label(state)?;
+    state
+        .get_project_detail(path, root)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown project: {}", path)))
+}
+
+/// API: All sessions of a project with daily usage (`/api/projects/%2FUsers%2Fme%2Fapp`)
+async fn project_detail_handler(
+    State(state): State<SharedState>,
+    Path(path): Path<String>,
+    Query(filter): Query<RootFilter>,
+) -> Result<Json<ProjectDetail>, (StatusCode, String)> {
+    let state = state.read().await;
+    find_project_detail(&state, &path, &filter).map(Json)
+}
+
+/// Project page with session history
+async fn project_page_handler(
+    State(state): State<SharedState>,
+    Path(path): Path<String>,
+    Query(filter): Query<RootFilter>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let state = state.read().await;
+    let detail = find_project_detail(&state, &path, &filter)?;
+    Ok(Html(templates::render_project_page(
+        &detail,
+        state.config.roots.len() > 1,
+    )))
+}
+
+/// API: Usage blocks, oldest first, optionally for one data root (`?root=work`)
+async fn blocks_handler(
+    State(state): State<SharedState>,
+    Query(filter): Query<RootFilter>,
+) -> Result<Json<Vec<SessionBlock>>, (StatusCode, String)> {
+    let state = state.read().await;
+    let root = filter.label(&state)?;
+    Ok(Json(state.get_blocks(root)))
+}
+
+/// Query parameters of `/api/tools`
+#[derive(Debug, Deserialize)]
+struct ToolsQuery {
+    /// Exact project path
+    project: Option<String>,
+    #[serde(flatten)]
+    filter: RootFilter,
+}
+
+/// API: Tool calls, failure rates and turn cost per tool, most called first,
+/// optionally for one project (`?project=/Users/me/app`) and/or data root
+async fn tools_handler(
+    State(state): State<SharedState>,
+    Query(query): Query<ToolsQuery>,
+) -> Result<Json<Vec<ToolSummary>>, (StatusCode, String)> {
+    let state = state.read().await;
+    let root = query.filter.label(&state)?;
+    Ok(Json(state.get_tools(root, query.project.as_deref())))
+}
+
+/// Query parameters of `/api/events/recent`
+#[derive(Debug, Deserialize)]
+struct RecentEventsQuery {
+    limit: Option<usize>,
+    #[serde(flatten)]
+    filter: RootFilter,
+}
+
+/// API: Recent session lifecycle events (started, idle, resumed, ended, agent
+/// spawned and finished), newest first, e.g. `?limit=20&root=work`
+async fn recent_events_handler(
+    State(state): State<SharedState>,
+    Query(query): Query<RecentEventsQuery>,
+) -> Result<Json<Vec<SessionEvent>>, (StatusCode, String)> {
     let state = state.read().await;
-    let sessions: Vec<_> = state.get_active_sessions().into_iter().cloned().collect();
-    Json(sessions)
+    let root = query.filter.label(&state)?;
+    let limit = query.limit.unwrap_or(DEFAULT_EVENTS_LIMIT);
+    Ok(Json(state.recent_events(root, limit)))
+}
+
+/// Query parameters of `/api/history`; empty values (from the search form) are ignored
+#[derive(Debug, Deserialize)]
+struct HistoryParams {
+    /// Search terms
+    q: Option<String>,
+    project: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    root: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl HistoryParams {
+    /// Validated search filters, offset and limit
+    fn query(
+        &self,
+        state: &AppState,
+    ) -> Result<(HistoryQuery, usize, usize), (StatusCode, String)> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_ref()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let bad_request = |e: String| (StatusCode::BAD_REQUEST, e);
+
+        let root = RootFilter {
+            root: non_empty(&self.root),
+        };
+        let query = HistoryQuery {
+            text: non_empty(&self.q),
+            project: non_empty(&self.project),
+            from: non_empty(&self.from)
+                .map(|v| timeseries::parse_time(&v))
+                .transpose()
+                .map_err(bad_request)?,
+            to: non_empty(&self.to)
+                .map(|v| timeseries::parse_end(&v))
+                .transpose()
+                .map_err(bad_request)?,
+            root: root.label(state)?.map(str::to_string),
+        };
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(MAX_HISTORY_LIMIT);
+        Ok((query, self.offset, limit))
+    }
+}
+
+/// API: Search the prompt history, newest first, e.g.
+/// `?q=migration&project=/Users/me/app&from=2025-01-01&to=2025-01-31&offset=0&limit=100`
+async fn history_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<HistoryPage>, (StatusCode, String)> {
+    let state = state.read().await;
+    let (query, offset, limit) = params.query(&state)?;
+    Ok(Json(state.search_history(&query, offset, limit)))
+}
+
+/// Prompt history browser
+async fn history_page_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let state = state.read().await;
+    let (query, offset, limit) = params.query(&state)?;
+    let page = state.search_history(&query, offset, limit);
+    Ok(Html(templates::render_history_page(
+        &page,
+        &params.q.unwrap_or_default(),
+        query.project.as_deref(),
+    )))
+}
+
+/// HTMX partial: Prompt history search results
+async fn history_partial_handler(
+    State(state): State<SharedState>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let state = state.read().await;
+    let (query, offset, limit) = params.query(&state)?;
+    let page = state.search_history(&query, offset, limit);
+    Ok(Html(templates::render_history_partial(&page)))
+}
+
+/// Query parameters of `/api/timeseries`
+#[derive(Debug, Deserialize)]
+struct TimeseriesQuery {
+    from: Option<String>,
+    to: Option<String>,
+    #[serde(default)]
+    bucket: Bucket,
+    group_by: Option<GroupBy>,
+    #[serde(flatten)]
+    filter: RootFilter,
+}
+
+/// API: Usage over time in hour/day/week buckets, e.g.
+/// `?from=2025-01-01&to=2025-02-01&bucket=day&group_by=model`
+async fn timeseries_handler(
+    State(state): State<SharedState>,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<Timeseries>, (StatusCode, String)> {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(timeseries::parse_time)
+            .transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))
+    };
+    let (from, to) = (parse(&query.from)?, parse(&query.to)?);
+
+    let state = state.read().await;
+    let root = query.filter.label(&state)?;
+    state
+        .get_timeseries(query.bucket, query.group_by, from, to, root)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+/// Prometheus metrics in the text exposition format
+async fn metrics_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let state = state.read().await;
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        super::metrics::render(&state),
+    )
+}
+
+/// API: Server status
+async fn status_handler(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    let state = state.read().await;
+    Json(serde_json::json!({
+        "pid": std::process::id(),
+        "started_at": state.started_at,
+        "uptime_secs": (chrono::Utc::now() - state.started_at).num_seconds(),
+        "sessions": state.sessions.len(),
+        "last_refresh": state.last_refresh,
+    }))
 }
 
 /// API: Force refresh
 async fn refresh_handler(State(state): State<SharedState>) -> impl IntoResponse {
     let mut state = state.write().await;
     match state.refresh().await {
-        Ok(_) => (StatusCode::OK, "Refreshed"),
+        Ok(changed) => {
+            state.publish_update(&changed);
+            (StatusCode::OK, "Refreshed")
+        }
         Err(e) => {
             tracing::error!("Refresh failed: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, "Refresh failed")
@@ -69,6 +409,24 @@ async fn refresh_handler(State(state): State<SharedState>) -> impl IntoResponse
     }
 }
 
+/// API: Live stream of stats updates (Server-Sent Events).
+/// Sends the current stats on connect, then one `stats` event per refresh.
+async fn events_handler(
+    State(state): State<SharedState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let (updates, initial) = {
+        let state = state.read().await;
+        (state.updates.subscribe(), state.stats_update(&[]))
+    };
+
+    // Lagging subscribers just skip to the next update
+    let stream = futures::stream::once(async move { initial })
+        .chain(BroadcastStream::new(updates).filter_map(|u| async move { u.ok() }))
+        .map(|update| Event::default().event("stats").json_data(update));
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
 /// HTMX partial: Budget section
 async fn budget_partial_handler(State(state): State<SharedState>) -> impl IntoResponse {
     let state = state.read().await;
@@ -86,6 +444,27 @@ async fn stats_partial_handler(State(state): State<SharedState>) -> impl IntoRes
 /// HTMX partial: Active sessions list
 async fn sessions_partial_handler(State(state): State<SharedState>) -> impl IntoResponse {
     let state = state.read().await;
-    let sessions = state.get_active_sessions();
-    Html(templates::render_sessions_partial(&sessions))
+    let sessions = state.get_active_sessions(None);
+    Html(templates::render_sessions_partial(&sessions, state.config.roots.len() > 1))
+}
+
+/// HTMX partial: Per-model usage
+async fn models_partial_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let stats = state.get_stats();
+    Html(templates::render_models_partial(&stats))
+}
+
+/// HTMX partial: Usage block timeline
+async fn blocks_partial_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let blocks = state.get_recent_blocks(templates::TIMELINE_BLOCKS);
+    Html(templates::render_blocks_partial(&blocks, state.config.token_limit))
+}
+
+/// HTMX partial: Tool usage
+async fn tools_partial_handler(State(state): State<SharedState>) -> impl IntoResponse {
+    let state = state.read().await;
+    let tools = state.get_tools(None, None);
+    Html(templates::render_tools_partial(&tools))
 }
@@ -1,24 +1,519 @@
-use std::path::PathBuf;
+use clap::Args;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::alerts::AlertConfig;
+use crate::parser::{DEFAULT_TOKEN_LIMIT, ROLLING_WINDOW_HOURS};
+use claude_monitor_core::config::{config_dir, default_roots, expand_tilde, resolve_roots};
+use claude_monitor_core::stats::{
+    StatsSettings, DEFAULT_ACTIVE_THRESHOLD_SECS, DEFAULT_BURN_RATE_INTERVALS,
+};
+
+pub use claude_monitor_core::config::{DataRoot, Source};
+
+/// Default web server port
+pub const DEFAULT_PORT: u16 = 3456;
+
+/// Prefix of environment variables overriding config values
+const ENV_PREFIX: &str = "CLAUDE_MONITOR_";
+
+/// Longest usage block and burn rate interval accepted, a year; longer ones
+/// overflow the date arithmetic
+const MAX_WINDOW_HOURS: i64 = 24 * 365;
+/// Longest active threshold accepted, a week
+const MAX_ACTIVE_THRESHOLD_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Recent intervals (minutes) to compute burn rates over, e.g. "15,60"
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct BurnRateIntervals(pub Vec<i64>);
+
+impl FromStr for BurnRateIntervals {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|m| {
+                m.trim()
+                    .parse()
+                    .map_err(|_| format!("invalid interval \"{}\"", m.trim()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for BurnRateIntervals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes: Vec<String> = self.0.iter().map(|m| m.to_string()).collect();
+        write!(f, "{}", minutes.join(","))
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    /// Path to Claude Code data directory
-    pub claude_dir: PathBuf,
-    /// Path to projects directory
-    pub projects_dir: PathBuf,
-    /// Path to history file
-    pub history_file: PathBuf,
+    /// Claude Code data directories to monitor
+    pub roots: Vec<DataRoot>,
+    /// Path to the user pricing overrides (JSON)
+    pub pricing_file: PathBuf,
+    /// Path to the persistent usage index (SQLite)
+    pub index_file: PathBuf,
+    /// Web server port
+    pub port: u16,
+    /// Token limit per usage block
+    pub token_limit: u64,
+    /// Usage block (and rolling window) length in hours
+    pub window_hours: i64,
+    /// Seconds since last activity for a session to count as active
+    pub active_threshold_secs: i64,
+    /// Minutes to compute burn rates over
+    pub burn_rate_intervals: BurnRateIntervals,
+    /// Budget alert thresholds and notifiers (config file only)
+    pub alerts: AlertConfig,
+    /// Config file that was looked for
+    pub config_file: PathBuf,
+    /// Where each setting's value came from, keyed by setting name
+    pub sources: BTreeMap<&'static str, Source>,
+}
+
+/// Config file contents; every setting is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    roots: Option<Vec<DataRoot>>,
+    pricing_file: Option<PathBuf>,
+    index_file: Option<PathBuf>,
+    port: Option<u16>,
+    token_limit: Option<u64>,
+    window_hours: Option<i64>,
+    active_threshold_secs: Option<i64>,
+    burn_rate_intervals: Option<BurnRateIntervals>,
+    alerts: Option<AlertConfig>,
+}
+
+/// Command line overrides, shared by all subcommands
+#[derive(Debug, Clone, Default, Args)]
+pub struct ConfigOverrides {
+    /// Config file to use instead of the default location
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Claude Code data directory to monitor; repeat for several
+    #[arg(long = "root", global = true, value_name = "[LABEL=]PATH")]
+    pub roots: Vec<DataRoot>,
+    /// Token limit for the rolling window
+    #[arg(long, global = true, value_name = "TOKENS")]
+    pub token_limit: Option<u64>,
+    /// Rolling window length in hours
+    #[arg(long, global = true, value_name = "HOURS")]
+    pub window_hours: Option<i64>,
+    /// Seconds since last activity for a session to count as active
+    #[arg(long, global = true, value_name = "SECS")]
+    pub active_threshold_secs: Option<i64>,
+    /// Minutes to compute burn rates over, comma-separated
+    #[arg(long, global = true, value_name = "MINUTES")]
+    pub burn_rate_intervals: Option<BurnRateIntervals>,
+}
+
+impl ConfigOverrides {
+    /// Arguments reproducing these overrides, for re-launching the binary
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(v) = &self.config {
+            args.extend(["--config".to_string(), v.display().to_string()]);
+        }
+        for root in &self.roots {
+            args.extend([
+                "--root".to_string(),
+                format!("{}={}", root.label, root.path.display()),
+            ]);
+        }
+        if let Some(v) = self.token_limit {
+            args.extend(["--token-limit".to_string(), v.to_string()]);
+        }
+        if let Some(v) = self.window_hours {
+            args.extend(["--window-hours".to_string(), v.to_string()]);
+        }
+        if let Some(v) = self.active_threshold_secs {
+            args.extend(["--active-threshold-secs".to_string(), v.to_string()]);
+        }
+        if let Some(v) = &self.burn_rate_intervals {
+            args.extend(["--burn-rate-intervals".to_string(), v.to_string()]);
+        }
+        args
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         let home = dirs::home_dir().expect("Could not find home directory");
-        let claude_dir = home.join(".claude");
+        let monitor_dir = config_dir().expect("Could not find config directory");
 
         Self {
-            projects_dir: claude_dir.join("projects"),
-            history_file: claude_dir.join("history.jsonl"),
-            claude_dir,
+            roots: default_roots().expect("Could not find home directory"),
+            pricing_file: monitor_dir.join("pricing.json"),
+            index_file: dirs::data_dir()
+                .unwrap_or_else(|| home.join(".local/share"))
+                .join("claude-monitor")
+                .join("index.db"),
+            port: DEFAULT_PORT,
+            token_limit: DEFAULT_TOKEN_LIMIT,
+            window_hours: ROLLING_WINDOW_HOURS,
+            active_threshold_secs: DEFAULT_ACTIVE_THRESHOLD_SECS,
+            burn_rate_intervals: BurnRateIntervals(DEFAULT_BURN_RATE_INTERVALS.to_vec()),
+            alerts: AlertConfig::default(),
+            config_file: monitor_dir.join("config.toml"),
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Build the effective configuration: defaults, then the config file,
+    /// then `CLAUDE_MONITOR_*` environment variables, then command line flags
+    pub fn load(
+        overrides: &ConfigOverrides,
+        cli_port: Option<u16>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config = Self::default();
+
+        if let Some(path) = env_var::<PathBuf>("CONFIG")? {
+            config.config_file = expand_tilde(&path);
+        }
+        if let Some(path) = &overrides.config {
+            config.config_file = expand_tilde(path);
+        }
+        let file = read_config_file(&config.config_file, overrides.config.is_some())?;
+
+        let mut sources = BTreeMap::new();
+
+        let (roots, source) = resolve_roots(config.roots, file.roots, &overrides.roots)?;
+        config.roots = roots;
+        sources.insert("roots", source);
+
+        config.pricing_file = expand_tilde(&layer(
+            &mut sources,
+            "pricing_file",
+            config.pricing_file,
+            file.pricing_file,
+            None,
+        )?);
+        config.index_file = expand_tilde(&layer(
+            &mut sources,
+            "index_file",
+            config.index_file,
+            file.index_file,
+            None,
+        )?);
+        config.port = layer(&mut sources, "port", config.port, file.port, cli_port)?;
+        config.token_limit = layer(
+            &mut sources,
+            "token_limit",
+            config.token_limit,
+            file.token_limit,
+            overrides.token_limit,
+        )?;
+        config.window_hours = layer(
+            &mut sources,
+            "window_hours",
+            config.window_hours,
+            file.window_hours,
+            overrides.window_hours,
+        )?;
+        config.active_threshold_secs = layer(
+            &mut sources,
+            "active_threshold_secs",
+            config.active_threshold_secs,
+            file.active_threshold_secs,
+            overrides.active_threshold_secs,
+        )?;
+        config.burn_rate_intervals = layer(
+            &mut sources,
+            "burn_rate_intervals",
+            config.burn_rate_intervals,
+            file.burn_rate_intervals,
+            overrides.burn_rate_intervals.clone(),
+        )?;
+        // Alerts are structured, so they can only be set in the config file
+        if let Some(alerts) = file.alerts {
+            config.alerts = alerts;
+            sources.insert("alerts", Source::File);
+        }
+
+        if !(1..=MAX_WINDOW_HOURS).contains(&config.window_hours) {
+            return Err(format!("window_hours must be between 1 and {}", MAX_WINDOW_HOURS).into());
+        }
+        if !(0..=MAX_ACTIVE_THRESHOLD_SECS).contains(&config.active_threshold_secs) {
+            return Err(format!(
+                "active_threshold_secs must be between 0 and {}",
+                MAX_ACTIVE_THRESHOLD_SECS
+            )
+            .into());
+        }
+        if config.burn_rate_intervals.0.is_empty()
+            || config
+                .burn_rate_intervals
+                .0
+                .iter()
+                .any(|m| !(1..=MAX_WINDOW_HOURS * 60).contains(m))
+        {
+            return Err(format!(
+                "burn_rate_intervals must be between 1 and {} minutes",
+                MAX_WINDOW_HOURS * 60
+            )
+            .into());
         }
+        config.alerts.validate()?;
+
+        config.sources = sources;
+        Ok(config)
+    }
+
+    /// Budget and activity settings for computing statistics
+    pub fn stats_settings(&self) -> StatsSettings {
+        StatsSettings {
+            token_limit: self.token_limit,
+            window_hours: self.window_hours,
+            active_threshold_secs: self.active_threshold_secs,
+            burn_rate_intervals: self.burn_rate_intervals.0.clone(),
+        }
+    }
+
+    /// Print the effective configuration and where each value came from
+    pub fn print(&self) {
+        let status = if self.config_file.exists() {
+            "loaded"
+        } else {
+            "not found"
+        };
+        println!("Config file: {} ({})\n", self.config_file.display(), status);
+
+        let roots = self
+            .roots
+            .iter()
+            .map(|r| format!("{}={}", r.label, r.path.display()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let values: [(&str, String); 9] = [
+            ("roots", roots),
+            ("pricing_file", self.pricing_file.display().to_string()),
+            ("index_file", self.index_file.display().to_string()),
+            ("port", self.port.to_string()),
+            ("token_limit", self.token_limit.to_string()),
+            ("window_hours", self.window_hours.to_string()),
+            ("active_threshold_secs", self.active_threshold_secs.to_string()),
+            ("burn_rate_intervals", self.burn_rate_intervals.to_string()),
+            ("alerts", self.alerts.to_string()),
+        ];
+
+        let width = values.iter().map(|(_, v)| v.len()).max().unwrap_or(0);
+        for (name, value) in values {
+            let source = self.sources.get(name).unwrap_or(&Source::Default);
+            println!("  {:<22} {:<width$}  ({})", name, value, source, width = width);
+        }
+    }
+}
+
+/// Resolve one setting: default < file < env < cli
+fn layer<T: FromStr>(
+    sources: &mut BTreeMap<&'static str, Source>,
+    name: &'static str,
+    default: T,
+    file: Option<T>,
+    cli: Option<T>,
+) -> Result<T, Box<dyn std::error::Error>>
+where
+    T::Err: fmt::Display,
+{
+    let env_name = format!("{}{}", ENV_PREFIX, name.to_ascii_uppercase());
+
+    let (value, source) = if let Some(v) = cli {
+        (v, Source::Cli)
+    } else if let Some(v) = env_var(&name.to_ascii_uppercase())? {
+        (v, Source::Env(env_name))
+    } else if let Some(v) = file {
+        (v, Source::File)
+    } else {
+        (default, Source::Default)
+    };
+
+    sources.insert(name, source);
+    Ok(value)
+}
+
+/// Read and parse `CLAUDE_MONITOR_<name>`, if set
+fn env_var<T: FromStr>(name: &str) -> Result<Option<T>, Box<dyn std::error::Error>>
+where
+    T::Err: fmt::Display,
+{
+    let var = format!("{}{}", ENV_PREFIX, name);
+    match std::env::var(&var) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("Invalid value for {}: {}", var, e).into()),
+        _ => Ok(None),
+    }
+}
+
+/// Parse the config file; a missing file is only an error if it was asked for explicitly
+fn read_config_file(path: &Path, required: bool) -> Result<FileConfig, Box<dyn std::error::Error>> {
+    if !path.exists() && !required {
+        return Ok(FileConfig::default());
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+    toml::from_str(&content)
+        .map_err(|e| format!("Invalid config file {}: {}", path.display(), e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Environment variables are process-wide; tests touching them take turns
+    static ENV: Mutex<()> = Mutex::new(());
+
+    const VARS: [&str; 6] = [
+        "CLAUDE_MONITOR_CONFIG",
+        "CLAUDE_MONITOR_TOKEN_LIMIT",
+        "CLAUDE_MONITOR_ACTIVE_THRESHOLD_SECS",
+        "CLAUDE_MONITOR_INDEX_FILE",
+        "CLAUDE_MONITOR_ROOTS",
+        "CLAUDE_CONFIG_DIR",
+    ];
+
+    /// Load a config with `file` as the config file and the given variables set
+    fn load(
+        file: &str,
+        env: &[(&str, &str)],
+        overrides: ConfigOverrides,
+        cli_port: Option<u16>,
+    ) -> Result<Config, String> {
+        let _guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, file).unwrap();
+
+        for var in VARS {
+            std::env::remove_var(var);
+        }
+        for (var, value) in env {
+            std::env::set_var(var, value);
+        }
+        let overrides = ConfigOverrides {
+            config: Some(path),
+            ..overrides
+        };
+        let config = Config::load(&overrides, cli_port).map_err(|e| e.to_string());
+        for (var, _) in env {
+            std::env::remove_var(var);
+        }
+        config
+    }
+
+    #[test]
+    fn settings_layer_file_env_and_command_line() {
+        let file = "token_limit = 1\nport = 4000";
+        let config = load(file, &[], ConfigOverrides::default(), None).unwrap();
+        assert_eq!(config.token_limit, 1);
+        assert_eq!(config.sources["token_limit"], Source::File);
+        assert_eq!(config.sources["window_hours"], Source::Default);
+
+        let env = [("CLAUDE_MONITOR_TOKEN_LIMIT", "2")];
+        let config = load(file, &env, ConfigOverrides::default(), None).unwrap();
+        assert_eq!(config.token_limit, 2);
+        assert_eq!(
+            config.sources["token_limit"],
+            Source::Env("CLAUDE_MONITOR_TOKEN_LIMIT".to_string())
+        );
+
+        let overrides = ConfigOverrides {
+            token_limit: Some(3),
+            ..Default::default()
+        };
+        let config = load(file, &env, overrides, Some(5000)).unwrap();
+        assert_eq!(config.token_limit, 3);
+        assert_eq!(config.sources["token_limit"], Source::Cli);
+        assert_eq!(config.port, 5000);
+        assert_eq!(config.sources["port"], Source::Cli);
+
+        let config = load(file, &env, ConfigOverrides::default(), None).unwrap();
+        assert_eq!(config.port, 4000);
+        assert_eq!(config.sources["port"], Source::File);
+    }
+
+    #[test]
+    fn claude_config_dir_only_replaces_the_default_roots() {
+        let file = "[[roots]]\nlabel = \"file\"\npath = \"/data/file\"";
+        let claude = ("CLAUDE_CONFIG_DIR", "/data/claude-work");
+        let monitor = ("CLAUDE_MONITOR_ROOTS", "env=/data/env");
+        let labels = |config: Config| -> Vec<String> {
+            config.roots.into_iter().map(|r| r.label).collect()
+        };
+
+        let config = load("", &[claude], ConfigOverrides::default(), None).unwrap();
+        assert_eq!(
+            config.sources["roots"],
+            Source::Env("CLAUDE_CONFIG_DIR".to_string())
+        );
+        assert_eq!(labels(config), vec!["claude-work"]);
+
+        let config = load(file, &[claude], ConfigOverrides::default(), None).unwrap();
+        assert_eq!(labels(config), vec!["file"]);
+
+        let config = load(file, &[claude, monitor], ConfigOverrides::default(), None).unwrap();
+        assert_eq!(labels(config), vec!["env"]);
+
+        let overrides = ConfigOverrides {
+            roots: vec!["cli=/data/cli".parse().unwrap()],
+            ..Default::default()
+        };
+        let config = load(file, &[claude, monitor], overrides, None).unwrap();
+        assert_eq!(labels(config), vec!["cli"]);
+    }
+
+    #[test]
+    fn paths_from_the_environment_expand_the_home_directory() {
+        let env = [("CLAUDE_MONITOR_INDEX_FILE", "~/monitor/index.db")];
+        let config = load("", &env, ConfigOverrides::default(), None).unwrap();
+        assert_eq!(
+            config.index_file,
+            dirs::home_dir().unwrap().join("monitor/index.db")
+        );
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let file = "active_threshold_secs = -1";
+        let error = load(file, &[], ConfigOverrides::default(), None).unwrap_err();
+        assert!(error.contains("active_threshold_secs"));
+
+        // Values that would overflow the date arithmetic
+        let file = "window_hours = 10000000000";
+        let error = load(file, &[], ConfigOverrides::default(), None).unwrap_err();
+        assert!(error.contains("window_hours"));
+        let env = [("CLAUDE_MONITOR_ACTIVE_THRESHOLD_SECS", "9999999999999")];
+        let error = load("", &env, ConfigOverrides::default(), None).unwrap_err();
+        assert!(error.contains("active_threshold_secs"));
+        let overrides = ConfigOverrides {
+            burn_rate_intervals: Some(BurnRateIntervals(vec![15, i64::MAX])),
+            ..Default::default()
+        };
+        let error = load("", &[], overrides, None).unwrap_err();
+        assert!(error.contains("burn_rate_intervals"));
+
+        // The limits themselves are fine
+        let file = "window_hours = 8760\nactive_threshold_secs = 604800";
+        assert!(load(file, &[], ConfigOverrides::default(), None).is_ok());
+
+        let env = [("CLAUDE_MONITOR_TOKEN_LIMIT", "lots")];
+        let error = load("", &env, ConfigOverrides::default(), None).unwrap_err();
+        assert!(error.contains("CLAUDE_MONITOR_TOKEN_LIMIT"));
     }
 }
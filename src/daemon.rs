@@ -0,0 +1,240 @@
+use claude_monitor_core::server::{http_get, process_alive};
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::config::ConfigOverrides;
+
+pub use claude_monitor_core::server::{pid_file_path, PidFile};
+
+/// Status reported by a running server at `/api/status`
+#[derive(Debug, Deserialize)]
+pub struct ServerStatus {
+    pub pid: u32,
+    pub uptime_secs: i64,
+    pub sessions: usize,
+    pub last_refresh: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub fn log_file_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("claude-monitor").join("claude-monitor.log"))
+}
+
+/// Re-launch this binary as a detached foreground server with output redirected to the log file.
+/// `port` is only passed on if it was given on the command line, so the server reports
+/// where its port came from. Returns the child's PID once it has written its PID file.
+pub fn spawn_background(
+    port: Option<u16>,
+    overrides: &ConfigOverrides,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    let log_path = log_file_path().ok_or("Could not determine log file location")?;
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(["start", "--foreground"])
+        .args(port.map(|p| ["--port".to_string(), p.to_string()]).into_iter().flatten())
+        .args(overrides.to_args())
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Detach from the terminal's session so closing it doesn't stop the server
+        unsafe {
+            command.pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            });
+        }
+    }
+
+    let mut child = command.spawn()?;
+
+    // Wait for the server to come up (it writes the PID file once it's listening)
+    for _ in 0..50 {
+        if let Some(status) = child.try_wait()? {
+            return Err(format!(
+                "server exited during startup ({}), see {}",
+                status,
+                log_path.display()
+            )
+            .into());
+        }
+        if PidFile::read().map(|p| p.pid) == Some(child.id()) {
+            return Ok(child.id());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    Err(format!("server did not start in time, see {}", log_path.display()).into())
+}
+
+/// Stop the running server, waiting for it to exit
+pub fn stop_server() {
+    let Some(pid_file) = PidFile::read() else {
+        println!("claude-monitor is not running");
+        return;
+    };
+
+    // The PID may have been reused since the file was written; never signal
+    // a process that isn't a claude-monitor server
+    if let Err(e) = check_server(&pid_file) {
+        eprintln!("Not stopping pid {}: {}", pid_file.pid, e);
+        return;
+    }
+
+    if let Err(e) = terminate(pid_file.pid) {
+        eprintln!("Failed to stop claude-monitor (pid {}): {}", pid_file.pid, e);
+        return;
+    }
+
+    for _ in 0..50 {
+        if !process_alive(pid_file.pid) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    if process_alive(pid_file.pid) {
+        eprintln!("claude-monitor (pid {}) did not exit in time", pid_file.pid);
+    } else {
+        // The server removes its PID file on shutdown; clean up in case it couldn't
+        if let Some(path) = pid_file_path() {
+            let _ = fs::remove_file(path);
+        }
+        println!("Stopped claude-monitor");
+    }
+}
+
+/// Make sure the process named by the PID file is our server: it reports the
+/// same PID at `/api/status`, or, if it doesn't respond (e.g. it hangs), runs
+/// the same executable as this one
+fn check_server(pid_file: &PidFile) -> Result<(), String> {
+    match query_status(pid_file.port) {
+        Ok(status) if status.pid == pid_file.pid => return Ok(()),
+        Ok(status) => {
+            return Err(format!("port {} is served by pid {}", pid_file.port, status.pid))
+        }
+        Err(_) => {}
+    }
+
+    let expected = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.file_stem().map(|n| n.to_string_lossy().into_owned()))
+        .ok_or("could not determine this executable's name")?;
+    match process_name(pid_file.pid) {
+        // Linux truncates process names to 15 characters
+        Some(name) if name == expected || (name.len() == 15 && expected.starts_with(&name)) => {
+            Ok(())
+        }
+        Some(name) => Err(format!("it is running {}", name)),
+        None => Err("could not determine what it is running".to_string()),
+    }
+}
+
+/// Executable name of a running process
+#[cfg(unix)]
+fn process_name(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let comm = String::from_utf8_lossy(&output.stdout);
+    // macOS reports the full path; Linux truncates names to 15 characters
+    let name = comm.trim().rsplit('/').next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(not(unix))]
+fn process_name(pid: u32) -> Option<String> {
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()?;
+    let row = String::from_utf8_lossy(&output.stdout);
+    let image = row.trim().strip_prefix('"')?.split('"').next()?;
+    Some(image.trim_end_matches(".exe").to_string())
+}
+
+/// Ask a process to shut down gracefully
+#[cfg(unix)]
+fn terminate(pid: u32) -> std::io::Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// The detached server has no window to close, so taskkill has to force it
+#[cfg(not(unix))]
+fn terminate(pid: u32) -> std::io::Result<()> {
+    let status = Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, status.to_string()))
+    }
+}
+
+/// Print status of the running server
+pub fn show_status() {
+    let Some(pid_file) = PidFile::read() else {
+        println!("claude-monitor is not running");
+        return;
+    };
+
+    println!("claude-monitor is running");
+    println!("  PID:      {}", pid_file.pid);
+    println!("  URL:      http://localhost:{}", pid_file.port);
+
+    match query_status(pid_file.port) {
+        Ok(status) => {
+            println!("  Uptime:   {}", format_duration(status.uptime_secs));
+            println!("  Sessions: {}", status.sessions);
+            if let Some(last_refresh) = status.last_refresh {
+                println!(
+                    "  Updated:  {}",
+                    last_refresh.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+            if status.pid != pid_file.pid {
+                println!("  Warning: port {} is served by pid {}", pid_file.port, status.pid);
+            }
+        }
+        Err(e) => {
+            println!("  Server not responding: {}", e);
+        }
+    }
+
+    if let Some(log) = log_file_path() {
+        println!("  Log:      {}", log.display());
+    }
+}
+
+/// Fetch `/api/status` from a server on localhost
+pub fn query_status(port: u16) -> Result<ServerStatus, Box<dyn std::error::Error>> {
+    let body = http_get(port, "/api/status")?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+fn format_duration(secs: i64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, secs % 60)
+    }
+}
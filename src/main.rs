@@ -1,17 +1,25 @@
+mod alerts;
 mod api;
 mod config;
+mod daemon;
+mod format;
 mod monitor;
-mod parser;
+mod report;
 mod web;
 
 use clap::{Parser, Subcommand};
+use claude_monitor_core::{parser, pricing};
+use std::io::IsTerminal;
 use std::process;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::config::Config;
+use crate::config::{Config, ConfigOverrides};
+use crate::daemon::PidFile;
 use crate::monitor::state::AppState;
+use crate::monitor::timeseries::Bucket;
+use crate::report::{Format, ReportOptions};
 
 #[derive(Parser)]
 #[command(name = "claude-monitor")]
@@ -19,15 +27,17 @@ use crate::monitor::state::AppState;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    #[command(flatten)]
+    overrides: ConfigOverrides,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Start the monitor server
     Start {
-        /// Port to listen on
-        #[arg(short, long, default_value = "3456")]
-        port: u16,
+        /// Port to listen on [default: 3456]
+        #[arg(short, long)]
+        port: Option<u16>,
         /// Run in foreground (don't daemonize)
         #[arg(short, long)]
         foreground: bool,
@@ -36,6 +46,38 @@ enum Commands {
     Stop,
     /// Show current status
     Status,
+    /// Print usage aggregated by day, week or month
+    Report {
+        /// Period to aggregate by
+        #[arg(value_enum, default_value_t = Bucket::Day)]
+        period: Bucket,
+        /// First day to include (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Last day to include (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Only include projects whose path contains this
+        #[arg(long)]
+        project: Option<String>,
+        /// Print JSON
+        #[arg(long, conflicts_with = "csv")]
+        json: bool,
+        /// Print CSV
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Inspect configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the effective configuration and where each value came from
+    Show,
 }
 
 #[tokio::main]
@@ -44,43 +86,109 @@ async fn main() {
 
     match cli.command {
         Some(Commands::Start { port, foreground }) => {
-            if !foreground {
-                // Check if already running
-                if is_running() {
-                    eprintln!("claude-monitor is already running");
+            let config = load_config(&cli.overrides, port);
+            if foreground {
+                start_server(config).await;
+                return;
+            }
+
+            // Check if already running
+            if let Some(running) = PidFile::read() {
+                eprintln!(
+                    "claude-monitor is already running (pid {}, port {})",
+                    running.pid, running.port
+                );
+                process::exit(1);
+            }
+
+            println!("Starting claude-monitor on port {}...", config.port);
+            match daemon::spawn_background(port, &cli.overrides) {
+                Ok(pid) => {
+                    println!("  Running in background (pid {})", pid);
+                    println!("  Open http://localhost:{} in your browser", config.port);
+                    if let Some(log) = daemon::log_file_path() {
+                        println!("  Logs: {}", log.display());
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to start claude-monitor: {}", e);
                     process::exit(1);
                 }
-
-                // For now, just run in foreground
-                // TODO: Implement proper daemonization
-                println!("Starting claude-monitor on port {}...", port);
             }
-            start_server(port).await;
         }
         Some(Commands::Stop) => {
-            stop_server();
+            daemon::stop_server();
         }
         Some(Commands::Status) => {
-            show_status();
+            daemon::show_status();
+        }
+        Some(Commands::Report {
+            period,
+            since,
+            until,
+            project,
+            json,
+            csv,
+        }) => {
+            let config = load_config(&cli.overrides, None);
+            let format = if json {
+                Format::Json
+            } else if csv {
+                Format::Csv
+            } else {
+                Format::Table
+            };
+            let options = ReportOptions {
+                period,
+                since,
+                until,
+                project,
+                format,
+            };
+            if let Err(e) = report::run(&config, &options).await {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        Some(Commands::Config {
+            command: ConfigCommand::Show,
+        }) => {
+            load_config(&cli.overrides, None).print();
         }
         None => {
             // Default: start in foreground
-            start_server(3456).await;
+            start_server(load_config(&cli.overrides, None)).await;
         }
     }
 }
 
-async fn start_server(port: u16) {
+/// Load the effective configuration, exiting on invalid config
+fn load_config(overrides: &ConfigOverrides, port: Option<u16>) -> Config {
+    Config::load(overrides, port).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
+}
+
+async fn start_server(config: Config) {
+    let port = config.port;
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "claude_monitor=info,tower_http=info".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_ansi(std::io::stdout().is_terminal()))
         .init();
 
-    let config = Config::default();
+    if let Some(running) = PidFile::read() {
+        eprintln!(
+            "claude-monitor is already running (pid {}, port {})",
+            running.pid, running.port
+        );
+        process::exit(1);
+    }
 
     // Initialize app state
     let state = Arc::new(RwLock::new(AppState::new(&config)));
@@ -88,11 +196,23 @@ async fn start_server(port: u16) {
     // Initial load of data
     {
         let mut state = state.write().await;
+        state.enable_alerts();
         if let Err(e) = state.refresh().await {
             tracing::error!("Failed to load initial data: {}", e);
         }
     }
 
+    // Block resets and sessions going quiet don't touch any files
+    let alert_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(alerts::CHECK_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            alert_state.write().await.check_alerts();
+        }
+    });
+
     // Start file watcher
     let watcher_state = state.clone();
     tokio::spawn(async move {
@@ -106,7 +226,17 @@ async fn start_server(port: u16) {
 
     // Start server
     let addr = format!("127.0.0.1:{}", port);
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Failed to listen on {}: {}", addr, e);
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = PidFile::write_current(port) {
+        tracing::warn!("Failed to write PID file: {}", e);
+    }
 
     tracing::info!("Claude Monitor running at http://{}", addr);
     println!("\n  Claude Monitor is running!");
@@ -117,6 +247,8 @@ async fn start_server(port: u16) {
         .with_graceful_shutdown(shutdown_signal())
         .await
         .unwrap();
+
+    PidFile::remove_current();
 }
 
 async fn shutdown_signal() {
@@ -144,51 +276,3 @@ async fn shutdown_signal() {
 
     tracing::info!("Shutting down...");
 }
-
-fn is_running() -> bool {
-    // Check for PID file
-    if let Some(pid_file) = get_pid_file() {
-        if pid_file.exists() {
-            if let Ok(pid) = std::fs::read_to_string(&pid_file) {
-                let pid = pid.trim();
-                // Check if process is running
-                let output = std::process::Command::new("kill")
-                    .args(["-0", pid])
-                    .output();
-                return output.map(|o| o.status.success()).unwrap_or(false);
-            }
-        }
-    }
-    false
-}
-
-fn stop_server() {
-    if let Some(pid_file) = get_pid_file() {
-        if pid_file.exists() {
-            if let Ok(pid) = std::fs::read_to_string(&pid_file) {
-                let pid = pid.trim();
-                let _ = std::process::Command::new("kill")
-                    .arg(pid)
-                    .status();
-                let _ = std::fs::remove_file(&pid_file);
-                println!("Stopped claude-monitor");
-                return;
-            }
-        }
-    }
-    println!("claude-monitor is not running");
-}
-
-fn show_status() {
-    if is_running() {
-        println!("claude-monitor is running");
-    } else {
-        println!("claude-monitor is not running");
-    }
-}
-
-fn get_pid_file() -> Option<std::path::PathBuf> {
-    dirs::runtime_dir()
-        .or_else(|| dirs::cache_dir())
-        .map(|d| d.join("claude-monitor.pid"))
-}
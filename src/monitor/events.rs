@@ -0,0 +1,278 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+use crate::parser::SessionData;
+
+/// Session lifecycle events kept for `/api/events/recent`
+pub const EVENT_LOG_CAPACITY: usize = 500;
+
+/// Seconds without activity after which an idle main session counts as ended
+pub const SESSION_END_SECS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEventKind {
+    /// A main session appeared and is active
+    SessionStarted,
+    /// A main session saw no activity for the active threshold
+    SessionIdle,
+    /// An idle or ended main session became active again
+    SessionResumed,
+    /// A main session saw no activity for [`SESSION_END_SECS`]
+    SessionEnded,
+    /// An agent session appeared (or came back) and is active
+    AgentSpawned,
+    /// An agent session saw no activity for the active threshold
+    AgentFinished,
+}
+
+/// A change in a session's activity between two refreshes
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent {
+    #[serde(rename = "type")]
+    pub kind: SessionEventKind,
+    /// When the change happened according to the transcript: the last
+    /// activity for starts, resumes and finished agents, or when the
+    /// session crossed the idle or end threshold
+    pub timestamp: DateTime<Utc>,
+    /// Key of the session in the app state
+    pub session_key: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub root: String,
+    pub is_agent: bool,
+}
+
+/// Where a session is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Active,
+    Idle,
+    Ended,
+}
+
+impl Status {
+    /// Status of a session at `now`; agents end as soon as they go quiet
+    fn of(session: &SessionData, active_threshold: Duration, now: DateTime<Utc>) -> Self {
+        let Some(last_activity) = session.last_activity else {
+            return Self::Ended;
+        };
+        let quiet = now - last_activity;
+        if quiet < active_threshold {
+            Self::Active
+        } else if session.is_agent || quiet >= Duration::seconds(SESSION_END_SECS) {
+            Self::Ended
+        } else {
+            Self::Idle
+        }
+    }
+}
+
+/// Session statuses as of the last snapshot and the most recent lifecycle
+/// events, oldest first
+#[derive(Debug)]
+pub struct EventLog {
+    statuses: HashMap<String, Status>,
+    events: VecDeque<SessionEvent>,
+    capacity: usize,
+    /// Whether a first snapshot was taken; it only records statuses
+    initialized: bool,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::with_capacity(EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            statuses: HashMap::new(),
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            initialized: false,
+        }
+    }
+
+    /// Compare `sessions` with the previous snapshot, record the resulting
+    /// events and return them. The first snapshot only records statuses, so
+    /// sessions that were running (or long over) at startup aren't reported.
+    pub fn update<'a>(
+        &mut self,
+        sessions: impl IntoIterator<Item = (&'a String, &'a SessionData)>,
+        active_threshold_secs: i64,
+        now: DateTime<Utc>,
+    ) -> Vec<SessionEvent> {
+        let active_threshold = Duration::seconds(active_threshold_secs);
+        let mut events = Vec::new();
+
+        for (key, session) in sessions {
+            let status = Status::of(session, active_threshold, now);
+            let previous = self.statuses.insert(key.clone(), status);
+            if !self.initialized || previous == Some(status) {
+                continue;
+            }
+
+            let last_activity = session.last_activity.unwrap_or(now);
+            let (kind, timestamp) = match (previous, status, session.is_agent) {
+                (None, Status::Active, false) => (SessionEventKind::SessionStarted, last_activity),
+                (_, Status::Active, true) => (SessionEventKind::AgentSpawned, last_activity),
+                (Some(_), Status::Active, false) => {
+                    (SessionEventKind::SessionResumed, last_activity)
+                }
+                (Some(Status::Active), Status::Idle, _) => (
+                    SessionEventKind::SessionIdle,
+                    last_activity + active_threshold,
+                ),
+                (Some(Status::Active), Status::Ended, true) => {
+                    (SessionEventKind::AgentFinished, last_activity)
+                }
+                (Some(_), Status::Ended, false) => (
+                    SessionEventKind::SessionEnded,
+                    last_activity + Duration::seconds(SESSION_END_SECS),
+                ),
+                // Sessions first seen when already quiet, e.g. old transcripts
+                // copied in, have nothing to report
+                _ => continue,
+            };
+
+            events.push(SessionEvent {
+                kind,
+                timestamp,
+                session_key: key.clone(),
+                session_id: session.session_id.clone(),
+                project_path: session.project_path.clone(),
+                root: session.root.clone(),
+                is_agent: session.is_agent,
+            });
+        }
+        self.initialized = true;
+
+        // Keep a stable order when several sessions change at once
+        events.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.session_key.cmp(&b.session_key))
+        });
+        for event in &events {
+            if self.events.len() == self.capacity {
+                self.events.pop_front();
+            }
+            self.events.push_back(event.clone());
+        }
+        events
+    }
+
+    /// Up to `limit` of the most recent events, newest first, limited to one
+    /// data root if `root` is given
+    pub fn recent(&self, root: Option<&str>, limit: usize) -> Vec<SessionEvent> {
+        self.events
+            .iter()
+            .rev()
+            .filter(|e| root.map(|label| e.root == label).unwrap_or(true))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn session(id: &str, last_activity: DateTime<Utc>, is_agent: bool) -> (String, SessionData) {
+        let path = PathBuf::from(format!("/projects/app/{}.jsonl", id));
+        let mut session = SessionData::from_path(&path, "default");
+        session.last_activity = Some(last_activity);
+        session.is_agent = is_agent;
+        (id.to_string(), session)
+    }
+
+    fn kinds(
+        log: &mut EventLog,
+        sessions: &[(String, SessionData)],
+        now: DateTime<Utc>,
+    ) -> Vec<(SessionEventKind, String)> {
+        log.update(sessions.iter().map(|(k, s)| (k, s)), 300, now)
+            .into_iter()
+            .map(|e| (e.kind, e.session_key))
+            .collect()
+    }
+
+    #[test]
+    fn main_sessions_go_idle_resume_and_end() {
+        let start = Utc::now();
+        let minutes = |m| start + Duration::minutes(m);
+        let mut log = EventLog::default();
+
+        // Running at startup: nothing to report
+        assert!(kinds(&mut log, &[session("a", start, false)], start).is_empty());
+
+        assert_eq!(
+            kinds(&mut log, &[session("a", start, false)], minutes(6)),
+            vec![(SessionEventKind::SessionIdle, "a".to_string())]
+        );
+        assert_eq!(
+            kinds(&mut log, &[session("a", minutes(7), false)], minutes(7)),
+            vec![(SessionEventKind::SessionResumed, "a".to_string())]
+        );
+        assert!(kinds(&mut log, &[session("a", minutes(7), false)], minutes(8)).is_empty());
+        assert_eq!(
+            kinds(&mut log, &[session("a", minutes(7), false)], minutes(40)),
+            vec![(SessionEventKind::SessionEnded, "a".to_string())]
+        );
+
+        let recent = log.recent(None, 10);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].kind, SessionEventKind::SessionEnded);
+        assert_eq!(recent[0].timestamp, minutes(37));
+    }
+
+    #[test]
+    fn agents_spawn_and_finish() {
+        let start = Utc::now();
+        let later = start + Duration::minutes(10);
+        let mut log = EventLog::default();
+        kinds(&mut log, &[], start);
+
+        assert_eq!(
+            kinds(
+                &mut log,
+                &[session("main", start, false), session("agent", start, true)],
+                start
+            ),
+            vec![
+                (SessionEventKind::AgentSpawned, "agent".to_string()),
+                (SessionEventKind::SessionStarted, "main".to_string()),
+            ]
+        );
+        assert_eq!(
+            kinds(
+                &mut log,
+                &[session("main", later, false), session("agent", start, true)],
+                later
+            ),
+            vec![(SessionEventKind::AgentFinished, "agent".to_string())]
+        );
+    }
+
+    #[test]
+    fn the_log_keeps_the_latest_events() {
+        let now = Utc::now();
+        let mut log = EventLog::with_capacity(2);
+        kinds(&mut log, &[], now);
+
+        for id in ["a", "b", "c"] {
+            kinds(&mut log, &[session(id, now, false)], now);
+        }
+        let recent: Vec<String> = log
+            .recent(None, 5)
+            .into_iter()
+            .map(|e| e.session_key)
+            .collect();
+        assert_eq!(recent, vec!["c", "b"]);
+    }
+}
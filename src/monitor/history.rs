@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::config::DataRoot;
+use crate::parser::history::{self, HistoryEntry};
+
+/// Filters for a prompt history search
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Whitespace-separated terms that must all appear in the prompt (case-insensitive)
+    pub text: Option<String>,
+    /// Exact project path
+    pub project: Option<String>,
+    /// Start of the range (inclusive)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range (exclusive)
+    pub to: Option<DateTime<Utc>>,
+    pub root: Option<String>,
+}
+
+/// One prompt typed into Claude Code
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptRecord {
+    /// Label of the data root whose history the prompt is from
+    pub root: String,
+    pub prompt: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub project: Option<String>,
+    pub session_id: Option<String>,
+    /// The session's transcript is loaded, so it can be linked to
+    pub session_available: bool,
+}
+
+/// A page of search results, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    /// Matching prompts in total
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub entries: Vec<PromptRecord>,
+    /// Every project in the history, for filtering
+    pub projects: Vec<String>,
+}
+
+/// Parsed history.jsonl of one data root
+#[derive(Debug)]
+struct HistoryFile {
+    root: String,
+    modified: Option<SystemTime>,
+    len: u64,
+    entries: Vec<HistoryEntry>,
+}
+
+/// Prompts from the history.jsonl of every data root
+#[derive(Debug, Default)]
+pub struct PromptHistory {
+    files: HashMap<PathBuf, HistoryFile>,
+}
+
+impl PromptHistory {
+    /// Re-read the history files of `roots` that changed since the last update
+    pub fn update(&mut self, roots: &[DataRoot]) {
+        for root in roots {
+            let path = root.history_file();
+            let Ok(metadata) = fs::metadata(&path) else {
+                self.files.remove(&path);
+                continue;
+            };
+            let modified = metadata.modified().ok();
+
+            let unchanged = self
+                .files
+                .get(&path)
+                .map(|f| f.modified == modified && f.len == metadata.len())
+                .unwrap_or(false);
+            if unchanged {
+                continue;
+            }
+
+            match history::parse_history_file(&path) {
+                Ok(entries) => {
+                    self.files.insert(
+                        path,
+                        HistoryFile {
+                            root: root.label.clone(),
+                            modified,
+                            len: metadata.len(),
+                            entries,
+                        },
+                    );
+                }
+                Err(e) => tracing::warn!("Failed to parse history file {:?}: {}", path, e),
+            }
+        }
+    }
+
+    /// Unique projects across all history files
+    pub fn projects(&self) -> Vec<String> {
+        let mut projects: Vec<String> = self
+            .files
+            .values()
+            .flat_map(|f| history::get_unique_projects(&f.entries))
+            .collect();
+        projects.sort();
+        projects.dedup();
+        projects
+    }
+
+    /// Prompts matching `query` with their data root, newest first
+    pub fn search<'a>(&'a self, query: &HistoryQuery) -> Vec<(&'a str, &'a HistoryEntry)> {
+        let terms: Vec<String> = query
+            .text
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut matches: Vec<(&str, &HistoryEntry)> = self
+            .files
+            .values()
+            .filter(|f| query.root.as_ref().map(|r| *r == f.root).unwrap_or(true))
+            .flat_map(|f| f.entries.iter().map(move |e| (f.root.as_str(), e)))
+            .filter(|(_, e)| {
+                query
+                    .project
+                    .as_ref()
+                    .map(|p| e.project.as_ref() == Some(p))
+                    .unwrap_or(true)
+            })
+            .filter(|(_, e)| {
+                let ts = e.timestamp_utc();
+                query
+                    .from
+                    .map(|f| ts.map(|t| t >= f).unwrap_or(false))
+                    .unwrap_or(true)
+                    && query
+                        .to
+                        .map(|t| ts.map(|ts| ts < t).unwrap_or(false))
+                        .unwrap_or(true)
+            })
+            .filter(|(_, e)| {
+                if terms.is_empty() {
+                    return true;
+                }
+                let prompt = e.display.as_deref().unwrap_or_default().to_lowercase();
+                terms.iter().all(|term| prompt.contains(term.as_str()))
+            })
+            .collect();
+
+        matches.sort_by_key(|(_, e)| std::cmp::Reverse(e.timestamp));
+        matches
+    }
+}
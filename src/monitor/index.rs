@@ -0,0 +1,394 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::monitor::state::FileCursor;
+use crate::parser::{SessionData, TimestampedUsage, TokenUsage};
+
+/// Bump when the parser changes how sessions or message keys are derived;
+/// files still on disk are then re-parsed while rows for deleted files are kept.
+const SCHEMA_VERSION: i32 = 7;
+
+/// Everything restored from the index at startup
+#[derive(Debug, Default)]
+pub struct IndexSnapshot {
+    pub files: Vec<(PathBuf, FileCursor)>,
+    /// Sessions, in the same order as `files`
+    pub sessions: Vec<SessionData>,
+    /// Timestamped usages with the session key they belong to
+    pub usages: Vec<(String, TimestampedUsage)>,
+}
+
+/// On-disk store of parsed usage, so startup doesn't re-read every transcript
+/// and totals survive Claude Code cleaning up old transcripts
+#[derive(Debug)]
+pub struct UsageIndex {
+    conn: Mutex<Connection>,
+    /// Index was written by an older schema version
+    outdated: bool,
+    /// Opened with [`UsageIndex::open_read_only`]; saving does nothing
+    read_only: bool,
+}
+
+impl UsageIndex {
+    /// Open (or create) the index database
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS files (
+                 path TEXT PRIMARY KEY,
+                 session_key TEXT NOT NULL,
+                 offset INTEGER NOT NULL,
+                 file_id INTEGER NOT NULL,
+                 session TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS usages (
+                 path TEXT NOT NULL,
+                 message_key TEXT NOT NULL,
+                 session_key TEXT NOT NULL,
+                 timestamp TEXT NOT NULL,
+                 model TEXT NOT NULL,
+                 input_tokens INTEGER NOT NULL,
+                 output_tokens INTEGER NOT NULL,
+                 cache_creation_input_tokens INTEGER NOT NULL,
+                 cache_read_input_tokens INTEGER NOT NULL,
+                 git_branch TEXT,
+                 PRIMARY KEY (path, message_key)
+             );",
+        )?;
+
+        // Indexes created before usages recorded the git branch
+        let has_branch: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('usages') WHERE name = 'git_branch')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_branch {
+            conn.execute_batch("ALTER TABLE usages ADD COLUMN git_branch TEXT")?;
+        }
+
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let has_rows: bool =
+            conn.query_row("SELECT EXISTS(SELECT 1 FROM files)", [], |row| row.get(0))?;
+        let outdated = has_rows && version < SCHEMA_VERSION;
+        conn.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            outdated,
+            read_only: false,
+        })
+    }
+
+    /// Open an existing index without changing it, e.g. for one-off reports
+    /// while a server may be writing to it
+    pub fn open_read_only(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            outdated: version < SCHEMA_VERSION,
+            read_only: true,
+        })
+    }
+
+    /// The connection, even if a thread panicked while holding it: every
+    /// write runs in a transaction that rolls back when dropped, so the
+    /// database is never left half-written
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Load all indexed files, sessions and usages
+    pub fn load(&self) -> Result<IndexSnapshot, Box<dyn std::error::Error>> {
+        let conn = self.conn();
+        let mut snapshot = IndexSnapshot::default();
+
+        let mut stmt = conn.prepare("SELECT path, session_key, offset, file_id, session FROM files")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (path, key, offset, file_id, session) = row?;
+            let session: SessionData = match serde_json::from_str(&session) {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable index entry for {}: {}", path, e);
+                    continue;
+                }
+            };
+
+            snapshot.files.push((
+                PathBuf::from(path),
+                FileCursor {
+                    key,
+                    offset: offset as u64,
+                    file_id: file_id as u64,
+                    stale: self.outdated,
+                },
+            ));
+            snapshot.sessions.push(session);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT session_key, message_key, timestamp, model, input_tokens, output_tokens,
+                    cache_creation_input_tokens, cache_read_input_tokens, git_branch
+             FROM usages",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                TokenUsage {
+                    input_tokens: row.get::<_, i64>(4)? as u64,
+                    output_tokens: row.get::<_, i64>(5)? as u64,
+                    cache_creation_input_tokens: row.get::<_, i64>(6)? as u64,
+                    cache_read_input_tokens: row.get::<_, i64>(7)? as u64,
+                },
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (key, message_key, timestamp, model, usage, git_branch) = row?;
+            let Ok(timestamp) = DateTime::parse_from_rfc3339(&timestamp) else {
+                continue;
+            };
+
+            snapshot.usages.push((
+                key,
+                TimestampedUsage {
+                    timestamp: timestamp.with_timezone(&Utc),
+                    usage,
+                    model,
+                    message_key,
+                    git_branch,
+                },
+            ));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Store the state of a session file and its newly parsed usages.
+    /// With `replace`, previously stored usages of the file are dropped first.
+    pub fn save_file(
+        &self,
+        path: &Path,
+        cursor: &FileCursor,
+        session: &SessionData,
+        usages: &[TimestampedUsage],
+        replace: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.read_only {
+            return Ok(());
+        }
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let path = path.to_string_lossy();
+
+        if replace {
+            tx.execute("DELETE FROM usages WHERE path = ?1", params![path])?;
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO files (path, session_key, offset, file_id, session)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                path,
+                cursor.key,
+                cursor.offset as i64,
+                cursor.file_id as i64,
+                serde_json::to_string(session)?,
+            ],
+        )?;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO usages (path, message_key, session_key, timestamp, model,
+                     input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens,
+                     git_branch)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for tu in usages {
+                stmt.execute(params![
+                    path,
+                    tu.message_key,
+                    cursor.key,
+                    tu.timestamp.to_rfc3339(),
+                    tu.model,
+                    tu.usage.input_tokens as i64,
+                    tu.usage.output_tokens as i64,
+                    tu.usage.cache_creation_input_tokens as i64,
+                    tu.usage.cache_read_input_tokens as i64,
+                    tu.git_branch,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn session(path: &Path) -> (FileCursor, SessionData, Vec<TimestampedUsage>) {
+        let mut session = SessionData::from_path(path, "default");
+        session.usage.input_tokens = 30;
+        let usages = (1..=2)
+            .map(|i| TimestampedUsage {
+                timestamp: Utc.with_ymd_and_hms(2025, 9, 1, 9, i, 0).unwrap(),
+                usage: TokenUsage {
+                    input_tokens: 10 * i as u64,
+                    ..Default::default()
+                },
+                model: "claude-sonnet-4-5".to_string(),
+                message_key: format!("msg-{}", i),
+                git_branch: Some("main".to_string()),
+            })
+            .collect();
+        let cursor = FileCursor {
+            key: session.key(),
+            offset: 42,
+            file_id: 7,
+            stale: false,
+        };
+        (cursor, session, usages)
+    }
+
+    #[test]
+    fn saved_files_are_restored_after_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("index.db");
+        let path = dir.path().join("projects/-src-app/a.jsonl");
+        let (cursor, session, usages) = session(&path);
+
+        let index = UsageIndex::open(&db).unwrap();
+        index
+            .save_file(&path, &cursor, &session, &usages[..1], true)
+            .unwrap();
+        index
+            .save_file(&path, &cursor, &session, &usages[1..], false)
+            .unwrap();
+        drop(index);
+
+        let snapshot = UsageIndex::open(&db).unwrap().load().unwrap();
+        assert_eq!(snapshot.files.len(), 1);
+        let (restored_path, restored) = &snapshot.files[0];
+        assert_eq!(restored_path, &path);
+        assert_eq!(restored.key, cursor.key);
+        assert_eq!((restored.offset, restored.file_id), (42, 7));
+        assert!(!restored.stale);
+        assert_eq!(snapshot.sessions[0].usage.input_tokens, 30);
+
+        assert_eq!(snapshot.usages.len(), 2);
+        assert!(snapshot.usages.iter().all(|(key, _)| key == &cursor.key));
+        let total: u64 = snapshot
+            .usages
+            .iter()
+            .map(|(_, tu)| tu.usage.input_tokens)
+            .sum();
+        assert_eq!(total, 30);
+        assert_eq!(snapshot.usages[0].1.git_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn replacing_a_file_drops_its_usages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.jsonl");
+        let (cursor, session, usages) = session(&path);
+
+        let index = UsageIndex::open(&dir.path().join("index.db")).unwrap();
+        index
+            .save_file(&path, &cursor, &session, &usages, true)
+            .unwrap();
+        index
+            .save_file(&path, &cursor, &session, &usages[1..], true)
+            .unwrap();
+
+        let snapshot = index.load().unwrap();
+        assert_eq!(snapshot.usages.len(), 1);
+        assert_eq!(snapshot.usages[0].1.message_key, "msg-2");
+    }
+
+    #[test]
+    fn older_schema_versions_mark_files_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("index.db");
+        let path = dir.path().join("a.jsonl");
+        let (cursor, session, usages) = session(&path);
+
+        let index = UsageIndex::open(&db).unwrap();
+        index
+            .save_file(&path, &cursor, &session, &usages, true)
+            .unwrap();
+        index
+            .conn()
+            .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION - 1))
+            .unwrap();
+        drop(index);
+
+        // Rows are kept, so deleted transcripts still count, but are re-parsed if possible
+        let snapshot = UsageIndex::open(&db).unwrap().load().unwrap();
+        assert!(snapshot.files[0].1.stale);
+        assert_eq!(snapshot.usages.len(), 2);
+
+        // The version was bumped, so the next start trusts the index again
+        let snapshot = UsageIndex::open(&db).unwrap().load().unwrap();
+        assert!(!snapshot.files[0].1.stale);
+    }
+
+    #[test]
+    fn read_only_indexes_are_left_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("index.db");
+        let path = dir.path().join("a.jsonl");
+        let (cursor, session, usages) = session(&path);
+        assert!(UsageIndex::open_read_only(&db).is_err());
+
+        let index = UsageIndex::open(&db).unwrap();
+        index.save_file(&path, &cursor, &session, &usages[..1], true).unwrap();
+        drop(index);
+
+        let index = UsageIndex::open_read_only(&db).unwrap();
+        index.save_file(&path, &cursor, &session, &usages, true).unwrap();
+        assert_eq!(index.load().unwrap().usages.len(), 1);
+        assert_eq!(UsageIndex::open(&db).unwrap().load().unwrap().usages.len(), 1);
+    }
+
+    #[test]
+    fn a_poisoned_lock_still_gives_the_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = UsageIndex::open(&dir.path().join("index.db")).unwrap();
+
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _conn = index.conn();
+            panic!("poison the lock");
+        }));
+        assert!(index.conn.is_poisoned());
+        assert!(index.load().unwrap().files.is_empty());
+    }
+}
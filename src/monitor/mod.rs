@@ -1,4 +1,8 @@
+pub mod events;
+pub mod history;
+pub mod index;
 pub mod state;
 pub mod watcher;
 
+pub use claude_monitor_core::{blocks, detail, timeseries, tools};
 pub use state::AppState;
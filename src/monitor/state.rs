@@ -1,194 +1,921 @@
-use crate::config::Config;
-use crate::parser::{
-    self, BudgetInfo, SessionData, TimestampedUsage, TokenUsage, DEFAULT_TOKEN_LIMIT,
-    ROLLING_WINDOW_HOURS,
-};
+use crate::alerts::Alerter;
+use crate::config::{Config, DataRoot};
+use crate::monitor::blocks::{self, SessionBlock};
+use crate::monitor::detail::{self, ProjectDetail, SessionDetail};
+use crate::monitor::events::{EventLog, SessionEvent};
+use crate::monitor::history::{HistoryPage, HistoryQuery, PromptHistory, PromptRecord};
+use crate::monitor::index::UsageIndex;
+use crate::monitor::timeseries::{Bucket, GroupBy, Timeseries};
+use crate::monitor::tools::{self, ToolSummary};
+use crate::parser::session::is_session_file;
+use crate::parser::{self, SessionData, TimestampedUsage};
+use crate::pricing::PricingTable;
 use chrono::{DateTime, Duration, Utc};
+use claude_monitor_core::stats::{self, RootStats};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+pub use claude_monitor_core::stats::Stats;
+
+/// Days of daily usage shown for a project
+const PROJECT_CHART_DAYS: i64 = 30;
 
 /// Application state holding all monitoring data
 #[derive(Debug)]
 pub struct AppState {
     pub config: Config,
+    pub pricing: PricingTable,
     pub sessions: HashMap<String, SessionData>,
-    pub timestamped_usages: Vec<TimestampedUsage>,
+    /// Timestamped usages keyed by session key
+    pub timestamped_usages: HashMap<String, Vec<TimestampedUsage>>,
+    /// Read position of every parsed session file
+    pub files: HashMap<PathBuf, FileCursor>,
+    /// Persistent usage index, if it could be opened
+    pub index: Option<UsageIndex>,
+    /// Live updates published after each refresh
+    pub updates: broadcast::Sender<StatsUpdate>,
+    pub started_at: DateTime<Utc>,
     pub last_refresh: Option<DateTime<Utc>>,
+    /// Counters describing refresh work, exported at `/metrics`
+    pub metrics: RefreshMetrics,
+    /// Prompts from every data root's history.jsonl
+    pub history: PromptHistory,
+    /// Session lifecycle events found by comparing successive refreshes
+    pub events: EventLog,
+    /// Budget alerts checked after each refresh; only the server enables them
+    pub alerter: Option<Alerter>,
+    /// Models already reported as missing from the pricing table
+    unpriced_models: HashSet<String>,
 }
 
-/// Summary statistics for the dashboard
-#[derive(Debug, Clone, Serialize)]
-pub struct Stats {
-    pub total_usage: TokenUsage,
-    pub rolling_usage: TokenUsage,
-    pub budget: BudgetInfo,
-    pub active_sessions: u32,
-    pub active_agents: u32,
-    pub total_messages: u32,
-    pub projects: Vec<ProjectStats>,
+/// Running totals of refresh work since startup
+#[derive(Debug, Clone, Default)]
+pub struct RefreshMetrics {
+    /// Successful full and incremental refreshes
+    pub refreshes: u64,
+    /// Refreshes that failed (e.g. unreadable projects directory)
+    pub refresh_errors: u64,
+    pub refresh_seconds_total: f64,
+    pub last_refresh_seconds: f64,
+    /// Session files that couldn't be read or parsed
+    pub parse_errors: u64,
 }
 
+impl RefreshMetrics {
+    fn record_refresh(&mut self, started: Instant, succeeded: bool) {
+        let elapsed = started.elapsed().as_secs_f64();
+        if succeeded {
+            self.refreshes += 1;
+        } else {
+            self.refresh_errors += 1;
+        }
+        self.refresh_seconds_total += elapsed;
+        self.last_refresh_seconds = elapsed;
+    }
+}
+
+/// Update pushed to live subscribers after a refresh
 #[derive(Debug, Clone, Serialize)]
-pub struct ProjectStats {
-    pub path: String,
-    pub usage: TokenUsage,
-    pub session_count: u32,
-    pub message_count: u32,
+pub struct StatsUpdate {
+    pub stats: Stats,
+    /// Sessions that changed in this refresh
+    pub sessions: Vec<SessionData>,
+}
+
+/// How far a session file has been parsed
+#[derive(Debug, Clone)]
+pub struct FileCursor {
+    /// Session key the file was parsed into
+    pub key: String,
+    /// Byte offset just past the last parsed line
+    pub offset: u64,
+    /// File identity, used to detect a file replaced under the same name
+    pub file_id: u64,
+    /// Parsed by an older version; re-parse the whole file on next refresh
+    pub stale: bool,
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.ino()
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> u64 {
+    0
 }
 
 impl AppState {
     pub fn new(config: &Config) -> Self {
-        Self {
+        let index = UsageIndex::open(&config.index_file)
+            .map_err(|e| {
+                tracing::warn!(
+                    "Failed to open usage index {:?}, history won't persist: {}",
+                    config.index_file,
+                    e
+                );
+            })
+            .ok();
+        Self::with_index(config, index)
+    }
+
+    /// State that reads the usage index but never writes to it; data parsed
+    /// since the index was last updated only lives in memory
+    pub fn read_only(config: &Config) -> Self {
+        let index = UsageIndex::open_read_only(&config.index_file)
+            .map_err(|e| {
+                tracing::debug!("No usage index at {:?}: {}", config.index_file, e);
+            })
+            .ok();
+        Self::with_index(config, index)
+    }
+
+    fn with_index(config: &Config, index: Option<UsageIndex>) -> Self {
+        let pricing = PricingTable::load(&config.pricing_file).unwrap_or_else(|e| {
+            tracing::warn!(
+                "Failed to load pricing file {:?}, using defaults: {}",
+                config.pricing_file,
+                e
+            );
+            PricingTable::default()
+        });
+
+        let mut state = Self {
             config: config.clone(),
+            pricing,
             sessions: HashMap::new(),
-            timestamped_usages: Vec::new(),
+            timestamped_usages: HashMap::new(),
+            files: HashMap::new(),
+            index,
+            updates: broadcast::channel(16).0,
+            started_at: Utc::now(),
             last_refresh: None,
-        }
+            metrics: RefreshMetrics::default(),
+            history: PromptHistory::default(),
+            events: EventLog::default(),
+            alerter: None,
+            unpriced_models: HashSet::new(),
+        };
+        state.load_index();
+        state
     }
 
-    /// Refresh all data from disk
-    pub async fn refresh(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.sessions.clear();
-        self.timestamped_usages.clear();
+    /// Restore previously parsed data from the usage index
+    fn load_index(&mut self) {
+        let Some(index) = &self.index else {
+            return;
+        };
 
-        // Read all project directories
-        if self.config.projects_dir.exists() {
-            for project_entry in fs::read_dir(&self.config.projects_dir)? {
-                let project_entry = project_entry?;
-                let project_path = project_entry.path();
+        let snapshot = match index.load() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Failed to load usage index: {}", e);
+                return;
+            }
+        };
 
-                if !project_path.is_dir() {
+        // Keep the stored key: entries from older versions may have used another key format
+        let mut renamed = HashMap::new();
+        for ((path, mut cursor), mut session) in snapshot.files.into_iter().zip(snapshot.sessions) {
+            // The file's data root was relabelled: move the session to its new key
+            if let Some(label) = self.root_for(&path).map(|r| r.label.clone()) {
+                if label != session.root {
+                    session.root = label;
+                    let key = session.key();
+                    renamed.insert(std::mem::replace(&mut cursor.key, key), path.clone());
+                }
+            }
+            self.pricing.price_session(&mut session);
+            self.sessions.insert(cursor.key.clone(), session);
+            self.files.insert(path, cursor);
+        }
+        for (mut key, tu) in snapshot.usages {
+            if let Some(path) = renamed.get(&key) {
+                key = self.files[path].key.clone();
+            }
+            // Restore dedup state so repeated blocks appended later aren't counted again
+            if let Some(session) = self.sessions.get_mut(&key) {
+                session.seen_messages.insert(tu.message_key.clone());
+            }
+            self.timestamped_usages.entry(key).or_default().push(tu);
+        }
+
+        // Store relabelled sessions under their new keys, so the old ones don't linger
+        for path in renamed.values() {
+            let cursor = &self.files[path];
+            let usages = self
+                .timestamped_usages
+                .get(&cursor.key)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if let Err(e) = index.save_file(path, cursor, &self.sessions[&cursor.key], usages, true) {
+                tracing::warn!("Failed to update usage index for {:?}: {}", path, e);
+            }
+        }
+        if !renamed.is_empty() {
+            tracing::info!("Moved {} sessions to relabelled data roots", renamed.len());
+        }
+
+        tracing::info!("Loaded {} sessions from usage index", self.sessions.len());
+        let keys: Vec<String> = self.sessions.keys().cloned().collect();
+        self.warn_unpriced(&keys);
+    }
+
+    /// Log each model of the given sessions that has tokens but no pricing
+    /// entry, once per model, since its usage is counted as free
+    fn warn_unpriced(&mut self, keys: &[String]) {
+        for key in keys {
+            let Some(session) = self.sessions.get(key) else {
+                continue;
+            };
+            for (model, usage) in &session.models {
+                if usage.total() == 0
+                    || self.pricing.lookup(model).is_some()
+                    || !self.unpriced_models.insert(model.clone())
+                {
                     continue;
                 }
+                tracing::warn!(
+                    "No price for model {:?}, its usage is counted as $0; add it to {:?}",
+                    model,
+                    self.config.pricing_file
+                );
+            }
+        }
+    }
 
-                // Read all session files in this project
-                for session_entry in fs::read_dir(&project_path)? {
-                    let session_entry = session_entry?;
-                    let session_path = session_entry.path();
+    /// Check the budget against the configured alert thresholds after each refresh
+    pub fn enable_alerts(&mut self) {
+        self.alerter = Some(Alerter::new(&self.config));
+    }
+
+    /// Raise any budget alerts the latest data crossed, and session events
+    pub fn check_alerts(&mut self) {
+        let events = self.events.update(
+            &self.sessions,
+            self.config.active_threshold_secs,
+            Utc::now(),
+        );
 
-                    // Only process .jsonl files
-                    if session_path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
-                        continue;
+        if self.alerter.is_none() {
+            return;
+        }
+        let stats = self.get_stats();
+        if let Some(alerter) = &mut self.alerter {
+            alerter.check(&stats);
+            alerter.forward(&events, &self.sessions);
+        }
+    }
+
+    /// Up to `limit` of the most recent session lifecycle events, newest first,
+    /// limited to one data root if `root` is given
+    pub fn recent_events(&self, root: Option<&str>, limit: usize) -> Vec<SessionEvent> {
+        self.events.recent(root, limit)
+    }
+
+    /// Refresh all data from disk, parsing only what changed since the last refresh.
+    /// Sessions whose transcripts were deleted are kept so lifetime totals survive.
+    /// Returns the keys of sessions that changed.
+    pub async fn refresh(&mut self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let started = Instant::now();
+        let result = self.scan_roots();
+        self.history.update(&self.config.roots);
+        self.metrics.record_refresh(started, result.is_ok());
+
+        self.last_refresh = Some(Utc::now());
+        if let Ok(changed) = &result {
+            tracing::info!("Refreshed data: {} sessions loaded", self.sessions.len());
+            self.warn_unpriced(changed);
+            self.check_alerts();
+        }
+        result
+    }
+
+    /// Parse every session file of every data root
+    fn scan_roots(&mut self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut changed = Vec::new();
+
+        // Read all project directories of every data root
+        for root in self.config.roots.clone() {
+            let projects_dir = root.projects_dir();
+            if !projects_dir.exists() {
+                continue;
+            }
+
+            for session_path in parser::session::session_files(&projects_dir)? {
+                changed.extend(self.update_file(&session_path, &root.label));
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Refresh only the given session files (e.g. from watcher events).
+    /// Returns the keys of sessions that changed.
+    pub async fn refresh_paths(&mut self, paths: &[PathBuf]) -> Vec<String> {
+        let started = Instant::now();
+        let changed: Vec<String> = paths
+            .iter()
+            .filter(|p| is_session_file(p) && p.exists())
+            .filter_map(|p| {
+                let root = self.root_for(p)?.label.clone();
+                self.update_file(p, &root)
+            })
+            .collect();
+        // history.jsonl isn't watched, but a new prompt also appends to a transcript
+        self.history.update(&self.config.roots);
+
+        self.metrics.record_refresh(started, true);
+        self.last_refresh = Some(Utc::now());
+        tracing::debug!("Refreshed {} changed files", paths.len());
+        if !changed.is_empty() {
+            self.warn_unpriced(&changed);
+            self.check_alerts();
+        }
+        changed
+    }
+
+    /// Data root a session file belongs to (the innermost one if roots are nested)
+    pub fn root_for(&self, path: &Path) -> Option<&DataRoot> {
+        self.config
+            .roots
+            .iter()
+            .filter(|r| path.starts_with(r.projects_dir()))
+            .max_by_key(|r| r.path.components().count())
+    }
+
+    /// Parse new lines of a session file in data root `root`, or the whole file
+    /// if it was truncated or replaced. Returns the session key if anything was parsed.
+    fn update_file(&mut self, path: &Path, root: &str) -> Option<String> {
+        let metadata = match fs::metadata(path) {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::warn!("Failed to read metadata for {:?}: {}", path, e);
+                self.metrics.parse_errors += 1;
+                return None;
+            }
+        };
+        let file_id = file_id(&metadata);
+
+        let tail_from = self.files.get(path).and_then(|cursor| {
+            if cursor.stale || cursor.file_id != file_id || metadata.len() < cursor.offset {
+                None
+            } else {
+                Some(cursor.offset)
+            }
+        });
+
+        match tail_from {
+            // Unchanged since last parse
+            Some(offset) if offset == metadata.len() => None,
+            Some(offset) => {
+                let key = self.files[path].key.clone();
+                let session = self.sessions.get_mut(&key)?;
+                let project_path = session.project_path.clone();
+
+                match parser::session::parse_session_tail(path, session, offset) {
+                    // The project path (part of the key) was only now found in the
+                    // transcript; re-parse so everything is stored under the new key
+                    Ok(_) if session.project_path != project_path => {
+                        if let Some(cursor) = self.files.get_mut(path) {
+                            cursor.stale = true;
+                        }
+                        self.update_file(path, root)
                     }
+                    // Only an incomplete line was appended
+                    Ok((_, new_offset)) if new_offset == offset => None,
+                    Ok((timestamped, offset)) => {
+                        self.pricing.price_session(session);
+                        let cursor = self.files.get_mut(path).expect("cursor checked above");
+                        cursor.offset = offset;
 
-                    match parser::session::parse_session_file(&session_path) {
-                        Ok((session_data, timestamped)) => {
-                            let key = format!(
-                                "{}:{}",
-                                session_data.project_path, session_data.session_id
-                            );
-                            self.sessions.insert(key, session_data);
-                            self.timestamped_usages.extend(timestamped);
+                        if let Some(index) = &self.index {
+                            if let Err(e) =
+                                index.save_file(path, cursor, session, &timestamped, false)
+                            {
+                                tracing::warn!("Failed to update usage index for {:?}: {}", path, e);
+                            }
                         }
-                        Err(e) => {
-                            tracing::warn!(
-                                "Failed to parse session file {:?}: {}",
-                                session_path,
-                                e
-                            );
+
+                        self.timestamped_usages
+                            .entry(key.clone())
+                            .or_default()
+                            .extend(timestamped);
+                        Some(key)
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to parse session file {:?}: {}", path, e);
+                        self.metrics.parse_errors += 1;
+                        None
+                    }
+                }
+            }
+            None => {
+                self.remove_file(path);
+
+                match parser::session::parse_session_file(path, root) {
+                    Ok((mut session_data, timestamped, offset)) => {
+                        self.pricing.price_session(&mut session_data);
+                        let key = session_data.key();
+                        let cursor = FileCursor {
+                            key: key.clone(),
+                            offset,
+                            file_id,
+                            stale: false,
+                        };
+
+                        if let Some(index) = &self.index {
+                            if let Err(e) =
+                                index.save_file(path, &cursor, &session_data, &timestamped, true)
+                            {
+                                tracing::warn!("Failed to update usage index for {:?}: {}", path, e);
+                            }
                         }
+
+                        self.files.insert(path.to_path_buf(), cursor);
+                        self.sessions.insert(key.clone(), session_data);
+                        self.timestamped_usages.insert(key.clone(), timestamped);
+                        Some(key)
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to parse session file {:?}: {}", path, e);
+                        self.metrics.parse_errors += 1;
+                        None
                     }
                 }
             }
         }
+    }
 
-        self.last_refresh = Some(Utc::now());
-        tracing::info!("Refreshed data: {} sessions loaded", self.sessions.len());
-        Ok(())
+    /// Drop everything parsed from a session file before it's re-parsed
+    fn remove_file(&mut self, path: &Path) {
+        if let Some(cursor) = self.files.remove(path) {
+            self.sessions.remove(&cursor.key);
+            self.timestamped_usages.remove(&cursor.key);
+        }
     }
 
-    /// Get aggregated statistics
-    pub fn get_stats(&self) -> Stats {
-        let mut total_usage = TokenUsage::default();
-        let mut active_sessions = 0u32;
-        let mut active_agents = 0u32;
-        let mut total_messages = 0u32;
-        let mut project_map: HashMap<String, (TokenUsage, u32, u32)> = HashMap::new();
+    /// Build a live update for the given changed sessions
+    pub fn stats_update(&self, changed: &[String]) -> StatsUpdate {
+        StatsUpdate {
+            stats: self.get_stats(),
+            sessions: changed
+                .iter()
+                .filter_map(|key| self.sessions.get(key))
+                .cloned()
+                .collect(),
+        }
+    }
 
-        let now = Utc::now();
-        let window_start = now - Duration::hours(ROLLING_WINDOW_HOURS);
-
-        for session in self.sessions.values() {
-            total_usage += session.usage.clone();
-            total_messages += session.message_count;
-
-            // Check if session is active (last activity within 5 minutes)
-            let is_active = session
-                .last_activity
-                .map(|la| (now - la).num_seconds() < 300)
-                .unwrap_or(false);
-
-            if is_active {
-                if session.is_agent {
-                    active_agents += 1;
-                } else {
-                    active_sessions += 1;
-                }
+    /// Push an update to live subscribers, if there are any
+    pub fn publish_update(&self, changed: &[String]) {
+        if self.updates.receiver_count() > 0 {
+            let _ = self.updates.send(self.stats_update(changed));
+        }
+    }
+
+    /// Iterate over timestamped usages, limited to one data root if `root` is given
+    pub fn usages<'a>(
+        &'a self,
+        root: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a TimestampedUsage> {
+        self.timestamped_usages
+            .iter()
+            .filter(move |(key, _)| {
+                root.is_none() || self.sessions.get(*key).map(|s| s.root.as_str()) == root
+            })
+            .flat_map(|(_, usages)| usages)
+    }
+
+    /// Usage blocks, oldest first, limited to one data root if `root` is given
+    pub fn get_blocks(&self, root: Option<&str>) -> Vec<SessionBlock> {
+        blocks::compute_blocks(
+            self.usages(root),
+            self.config.window_hours,
+            &self.pricing,
+            Utc::now(),
+        )
+    }
+
+    /// The last `count` usage blocks, oldest first
+    pub fn get_recent_blocks(&self, count: usize) -> Vec<SessionBlock> {
+        blocks::recent_blocks(
+            self.usages(None),
+            self.config.window_hours,
+            &self.pricing,
+            Utc::now(),
+            count,
+        )
+    }
+
+    /// Usage in `[from, to)` split into time buckets, optionally grouped,
+    /// and limited to one data root if `root` is given. Fails if the range
+    /// takes too many buckets.
+    pub fn get_timeseries(
+        &self,
+        bucket: Bucket,
+        group_by: Option<GroupBy>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        root: Option<&str>,
+    ) -> Result<Timeseries, String> {
+        self.timeseries_matching(bucket, group_by, from, to, |session| {
+            root.is_none() || session.map(|s| s.root.as_str()) == root
+        })
+    }
+
+    /// Usage in `[from, to)` of the sessions accepted by `include`, split into time buckets
+    fn timeseries_matching(
+        &self,
+        bucket: Bucket,
+        group_by: Option<GroupBy>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        include: impl Fn(Option<&SessionData>) -> bool,
+    ) -> Result<Timeseries, String> {
+        let mut series = Timeseries::new(bucket, group_by, from, to);
+
+        for (key, usages) in &self.timestamped_usages {
+            let session = self.sessions.get(key);
+            if !include(session) {
+                continue;
             }
 
-            // Aggregate by project
-            let entry = project_map.entry(session.project_path.clone()).or_default();
-            entry.0 += session.usage.clone();
-            entry.1 += 1;
-            entry.2 += session.message_count;
+            for tu in usages {
+                if from.map(|f| tu.timestamp < f).unwrap_or(false)
+                    || to.map(|t| tu.timestamp >= t).unwrap_or(false)
+                {
+                    continue;
+                }
+
+                let group = match group_by {
+                    None => None,
+                    Some(GroupBy::Model) => Some(tu.model.as_str()),
+                    Some(GroupBy::Project) => Some(
+                        session
+                            .map(|s| s.project_path.as_str())
+                            .unwrap_or_default(),
+                    ),
+                    Some(GroupBy::SessionType) => Some(
+                        if session.map(|s| s.is_agent).unwrap_or(false) {
+                            "agent"
+                        } else {
+                            "main"
+                        },
+                    ),
+                };
+
+                let cost = self.pricing.cost(&tu.model, &tu.usage);
+                series.add(tu.timestamp, &tu.usage, cost, group);
+            }
         }
 
-        // Calculate rolling window usage
-        let mut rolling_usage = TokenUsage::default();
-        let mut oldest_in_window: Option<DateTime<Utc>> = None;
+        series.fill_gaps()?;
+        Ok(series)
+    }
+
+    /// Get aggregated statistics across all data roots
+    pub fn get_stats(&self) -> Stats {
+        self.get_stats_for(None)
+    }
 
-        for tu in &self.timestamped_usages {
-            if tu.timestamp >= window_start {
-                rolling_usage += tu.usage.clone();
-                if oldest_in_window.map(|o| tu.timestamp < o).unwrap_or(true) {
-                    oldest_in_window = Some(tu.timestamp);
+    /// Get aggregated statistics, limited to one data root if `root` is given
+    pub fn get_stats_for(&self, root: Option<&str>) -> Stats {
+        let roots: Vec<RootStats> = self
+            .config
+            .roots
+            .iter()
+            .filter(|r| root.map(|label| r.label == label).unwrap_or(true))
+            .map(|r| RootStats::new(r.label.clone(), r.path.clone()))
+            .collect();
+
+        let sessions = self
+            .sessions
+            .iter()
+            .filter(|(_, s)| root.map(|label| s.root == label).unwrap_or(true))
+            .map(|(key, s)| {
+                let usages = self
+                    .timestamped_usages
+                    .get(key)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                (s, usages)
+            });
+
+        stats::compute_stats(
+            sessions,
+            roots,
+            &self.config.stats_settings(),
+            &self.pricing,
+            Utc::now(),
+        )
+    }
+
+    /// Per-message timeline of a session, re-parsed from its transcript.
+    /// Falls back to indexed usage if the transcript was removed.
+    pub fn get_session_detail(
+        &self,
+        project: &str,
+        session_id: &str,
+        root: Option<&str>,
+    ) -> Option<SessionDetail> {
+        let session = self.sessions.values().find(|s| {
+            s.project_path == project
+                && s.session_id == session_id
+                && root.map(|label| s.root == label).unwrap_or(true)
+        })?;
+        let key = session.key();
+
+        let path = self
+            .files
+            .iter()
+            .find(|(_, cursor)| cursor.key == key)
+            .map(|(path, _)| path)
+            .filter(|path| path.exists());
+        if let Some(path) = path {
+            match parser::session::parse_session_messages(path) {
+                Ok(messages) => {
+                    return Some(SessionDetail::new(session.clone(), messages, &self.pricing))
                 }
+                Err(e) => tracing::warn!("Failed to parse session file {:?}: {}", path, e),
             }
         }
 
-        // Create budget info
-        let budget = BudgetInfo::new(rolling_usage.billable(), DEFAULT_TOKEN_LIMIT, oldest_in_window);
+        let usages = self
+            .timestamped_usages
+            .get(&key)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        Some(SessionDetail::from_usages(session.clone(), usages, &self.pricing))
+    }
 
-        let mut projects: Vec<ProjectStats> = project_map
-            .into_iter()
-            .map(|(path, (usage, session_count, message_count))| ProjectStats {
-                path,
-                usage,
-                session_count,
-                message_count,
-            })
+    /// All sessions of a project with its recent daily usage,
+    /// limited to one data root if `root` is given
+    pub fn get_project_detail(&self, path: &str, root: Option<&str>) -> Option<ProjectDetail> {
+        let matches = |s: &SessionData| {
+            s.project_path == path && root.map(|label| s.root == label).unwrap_or(true)
+        };
+        let sessions: Vec<SessionData> = self
+            .sessions
+            .values()
+            .filter(|s| matches(s))
+            .cloned()
             .collect();
+        if sessions.is_empty() {
+            return None;
+        }
+
+        let today = Bucket::Day.floor(Utc::now());
+        let daily = self.timeseries_matching(
+            Bucket::Day,
+            Some(GroupBy::SessionType),
+            // Midday avoids landing on the wrong date across DST changes
+            Some(Bucket::Day.floor(
+                today - Duration::days(PROJECT_CHART_DAYS - 1) + Duration::hours(12),
+            )),
+            Some(Bucket::Day.next(today)),
+            |session| session.map(matches).unwrap_or(false),
+        )
+        .expect("the project chart has a few dozen buckets");
 
-        // Sort by total tokens descending
-        projects.sort_by(|a, b| b.usage.total().cmp(&a.usage.total()));
+        let branches = detail::branch_stats(
+            self.timestamped_usages
+                .iter()
+                .filter(|(key, _)| self.sessions.get(*key).map(matches).unwrap_or(false))
+                .flat_map(|(key, usages)| usages.iter().map(move |tu| (key.as_str(), tu))),
+            &self.pricing,
+        );
+
+        Some(ProjectDetail::new(path.to_string(), sessions, branches, daily))
+    }
 
-        Stats {
-            total_usage,
-            rolling_usage,
-            budget,
-            active_sessions,
-            active_agents,
-            total_messages,
-            projects,
+    /// Tool calls across sessions, limited to one data root and/or project if given
+    pub fn get_tools(&self, root: Option<&str>, project: Option<&str>) -> Vec<ToolSummary> {
+        tools::summarize(self.sessions.values().filter(|s| {
+            root.map(|label| s.root == label).unwrap_or(true)
+                && project.map(|path| s.project_path == path).unwrap_or(true)
+        }))
+    }
+
+    /// Search the prompt history, newest first, returning `limit` results from `offset`
+    pub fn search_history(&self, query: &HistoryQuery, offset: usize, limit: usize) -> HistoryPage {
+        let matches = self.history.search(query);
+        let entries = matches
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(root, entry)| {
+                let session_available = match (&entry.project, &entry.session_id) {
+                    (Some(project), Some(id)) => self
+                        .sessions
+                        .contains_key(&parser::session::session_key(root, project, id)),
+                    _ => false,
+                };
+                PromptRecord {
+                    root: root.to_string(),
+                    prompt: entry.display.clone().unwrap_or_default(),
+                    timestamp: entry.timestamp_utc(),
+                    project: entry.project.clone(),
+                    session_id: entry.session_id.clone(),
+                    session_available,
+                }
+            })
+            .collect();
+
+        HistoryPage {
+            total: matches.len(),
+            offset,
+            limit,
+            entries,
+            projects: self.history.projects(),
         }
     }
 
-    /// Get list of active sessions
-    pub fn get_active_sessions(&self) -> Vec<&SessionData> {
+    /// Get list of active sessions, limited to one data root if `root` is given
+    pub fn get_active_sessions(&self, root: Option<&str>) -> Vec<&SessionData> {
         let now = Utc::now();
 
         let mut sessions: Vec<_> = self
             .sessions
             .values()
+            .filter(|s| root.map(|label| s.root == label).unwrap_or(true))
             .filter(|s| {
                 s.last_activity
-                    .map(|la| (now - la).num_seconds() < 300)
+                    .map(|la| (now - la).num_seconds() < self.config.active_threshold_secs)
                     .unwrap_or(false)
             })
             .collect();
 
-        sessions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
         sessions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assistant_line(minute: u32, input: u64) -> String {
+        format!(
+            r#"{{"type":"assistant","message":{{"role":"assistant","model":"claude-sonnet-4-5","usage":{{"input_tokens":{},"output_tokens":1}}}},"timestamp":"2025-09-01T09:{:02}:00.000Z"}}"#,
+            input, minute
+        ) + "\n"
+    }
+
+    /// State watching a single data root in `dir`, and the path of one transcript in it
+    fn state_in(dir: &Path) -> (AppState, PathBuf) {
+        labelled_state_in(dir, "default")
+    }
+
+    fn labelled_state_in(dir: &Path, label: &str) -> (AppState, PathBuf) {
+        let config = Config {
+            roots: vec![DataRoot {
+                label: label.to_string(),
+                path: dir.to_path_buf(),
+            }],
+            pricing_file: dir.join("pricing.json"),
+            index_file: dir.join("index.db"),
+            ..Config::default()
+        };
+        let project = dir.join("projects").join("-src-app");
+        fs::create_dir_all(&project).unwrap();
+        (AppState::new(&config), project.join("session.jsonl"))
+    }
+
+    fn input_tokens(state: &AppState) -> u64 {
+        state.sessions.values().map(|s| s.usage.input_tokens).sum()
+    }
+
+    #[tokio::test]
+    async fn appended_lines_are_parsed_from_the_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut state, path) = state_in(dir.path());
+        fs::write(&path, assistant_line(0, 100)).unwrap();
+        state.refresh().await.unwrap();
+        assert_eq!(input_tokens(&state), 100);
+
+        let mut content = fs::read_to_string(&path).unwrap();
+        content.push_str(&assistant_line(1, 20));
+        fs::write(&path, &content).unwrap();
+        let changed = state.refresh_paths(std::slice::from_ref(&path)).await;
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(input_tokens(&state), 120);
+        assert_eq!(state.files[&path].offset, content.len() as u64);
+        assert_eq!(state.timestamped_usages[&changed[0]].len(), 2);
+
+        // Nothing new: the cursor is already at the end
+        assert!(state.refresh_paths(std::slice::from_ref(&path)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn truncated_files_are_parsed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut state, path) = state_in(dir.path());
+        fs::write(&path, assistant_line(0, 100) + &assistant_line(1, 20)).unwrap();
+        state.refresh().await.unwrap();
+        assert_eq!(input_tokens(&state), 120);
+
+        fs::write(&path, assistant_line(2, 7)).unwrap();
+        state.refresh_paths(std::slice::from_ref(&path)).await;
+        assert_eq!(input_tokens(&state), 7);
+        assert_eq!(state.sessions.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn replaced_files_are_parsed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut state, path) = state_in(dir.path());
+        fs::write(&path, assistant_line(0, 100)).unwrap();
+        state.refresh().await.unwrap();
+
+        // Same length, new inode: only the file identity tells them apart
+        let replacement = dir.path().join("replacement.jsonl");
+        fs::write(&replacement, assistant_line(0, 300)).unwrap();
+        fs::rename(&replacement, &path).unwrap();
+        state.refresh_paths(std::slice::from_ref(&path)).await;
+
+        assert_eq!(input_tokens(&state), 300);
+        assert_eq!(state.timestamped_usages.values().flatten().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn deleted_transcripts_are_restored_from_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut state, path) = state_in(dir.path());
+        fs::write(&path, assistant_line(0, 100) + &assistant_line(1, 20)).unwrap();
+        state.refresh().await.unwrap();
+        let key = state.files[&path].key.clone();
+        drop(state);
+
+        fs::remove_file(&path).unwrap();
+        let (mut state, _) = state_in(dir.path());
+        assert_eq!(input_tokens(&state), 120);
+        assert_eq!(state.timestamped_usages[&key].len(), 2);
+
+        state.refresh().await.unwrap();
+        assert_eq!(input_tokens(&state), 120);
+    }
+
+    #[tokio::test]
+    async fn tool_calls_resume_after_a_restart() {
+        let block = |content: &str| {
+            format!(
+                r#"{{"type":"assistant","requestId":"req_1","message":{{"id":"msg_1","model":"claude-sonnet-4-5","content":[{}],"usage":{{"input_tokens":100,"output_tokens":1}}}},"timestamp":"2025-09-01T09:00:00.000Z"}}"#,
+                content
+            ) + "\n"
+        };
+        let tool_use = block(r#"{"type":"tool_use","id":"toolu_1","name":"Bash"}"#);
+        let text = block(r#"{"type":"text","text":"Running the tests"}"#);
+        let tool_result = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","is_error":true}]},"timestamp":"2025-09-01T09:01:00.000Z"}"#.to_string() + "\n";
+
+        let dir = tempfile::tempdir().unwrap();
+        let (mut state, path) = state_in(dir.path());
+        fs::write(&path, &tool_use).unwrap();
+        state.refresh().await.unwrap();
+        drop(state);
+
+        // The rest of the turn and the failed result arrive while restarting
+        let (mut state, _) = state_in(dir.path());
+        fs::write(&path, tool_use + &text + &tool_result).unwrap();
+        state.refresh().await.unwrap();
+
+        let bash = &state.get_tools(None, None)[0];
+        assert_eq!(
+            (bash.name.as_str(), bash.calls, bash.errors),
+            ("Bash", 1, 1)
+        );
+        assert_eq!(bash.usage.input_tokens, 100);
+        assert_eq!(input_tokens(&state), 100);
+    }
+
+    #[tokio::test]
+    async fn relabelled_roots_move_indexed_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut state, path) = state_in(dir.path());
+        fs::write(&path, assistant_line(0, 100)).unwrap();
+        state.refresh().await.unwrap();
+        drop(state);
+
+        fs::remove_file(&path).unwrap();
+        for _ in 0..2 {
+            let (state, _) = labelled_state_in(dir.path(), "work");
+            let key = &state.files[&path].key;
+            assert!(key.starts_with("work:"));
+            assert_eq!(state.sessions[key].root, "work");
+            assert_eq!(state.timestamped_usages[key].len(), 1);
+            assert_eq!(state.get_stats_for(Some("work")).total_usage.input_tokens, 100);
+        }
+
+        // The index itself was migrated
+        let snapshot = UsageIndex::open(&dir.path().join("index.db")).unwrap().load().unwrap();
+        assert!(snapshot.files[0].1.key.starts_with("work:"));
+        assert!(snapshot.usages.iter().all(|(key, _)| key.starts_with("work:")));
+    }
+}
@@ -1,21 +1,33 @@
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 
 use crate::monitor::AppState;
 
-/// Start watching the Claude directory for changes
+/// Start watching the projects directory of every data root for changes
 pub async fn start_watching(
     state: Arc<RwLock<AppState>>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let projects_dir = {
+    let projects_dirs: Vec<PathBuf> = {
         let state = state.read().await;
-        state.config.projects_dir.clone()
+        state.config.roots.iter().map(|r| r.projects_dir()).collect()
     };
 
-    if !projects_dir.exists() {
-        tracing::warn!("Projects directory does not exist: {:?}", projects_dir);
+    let projects_dirs: Vec<PathBuf> = projects_dirs
+        .into_iter()
+        .filter(|dir| {
+            let exists = dir.exists();
+            if !exists {
+                tracing::warn!("Projects directory does not exist: {:?}", dir);
+            }
+            exists
+        })
+        .collect();
+
+    if projects_dirs.is_empty() {
         return Ok(());
     }
 
@@ -30,31 +42,49 @@ pub async fn start_watching(
         Config::default().with_poll_interval(Duration::from_secs(2)),
     )?;
 
-    watcher.watch(&projects_dir, RecursiveMode::Recursive)?;
-
-    tracing::info!("Started watching {:?}", projects_dir);
+    for dir in &projects_dirs {
+        watcher.watch(dir, RecursiveMode::Recursive)?;
+        tracing::info!("Started watching {:?}", dir);
+    }
 
     // Keep watcher alive
     let _watcher = watcher;
 
-    // Debounce refresh - wait for events to settle before refreshing
-    let mut last_refresh = std::time::Instant::now();
+    // Debounce refresh - collect events for a short while before refreshing
     let debounce_duration = Duration::from_millis(500);
 
     loop {
         tokio::select! {
-            Some(_event) = rx.recv() => {
-                let now = std::time::Instant::now();
-                if now.duration_since(last_refresh) > debounce_duration {
-                    last_refresh = now;
+            Some(event) = rx.recv() => {
+                let mut paths: HashSet<PathBuf> = event.paths.into_iter().collect();
 
-                    // Delay a bit to let file writes complete
-                    tokio::time::sleep(Duration::from_millis(100)).await;
+                // Let file writes complete, then pick up everything that arrived meanwhile
+                tokio::time::sleep(debounce_duration).await;
+                while let Ok(event) = rx.try_recv() {
+                    paths.extend(event.paths);
+                }
+
+                // Directory changes (new or removed projects) need a rescan
+                let rescan = paths
+                    .iter()
+                    .any(|p| p.is_dir() || p.extension().is_none());
 
-                    let mut state = state.write().await;
-                    if let Err(e) = state.refresh().await {
-                        tracing::error!("Failed to refresh data: {}", e);
+                let mut state = state.write().await;
+                let changed = if rescan {
+                    match state.refresh().await {
+                        Ok(changed) => changed,
+                        Err(e) => {
+                            tracing::error!("Failed to refresh data: {}", e);
+                            continue;
+                        }
                     }
+                } else {
+                    let paths: Vec<PathBuf> = paths.into_iter().collect();
+                    state.refresh_paths(&paths).await
+                };
+
+                if !changed.is_empty() {
+                    state.publish_update(&changed);
                 }
             }
             _ = tokio::signal::ctrl_c() => {
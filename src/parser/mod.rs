@@ -1,7 +0,0 @@
-pub mod history;
-pub mod session;
-
-pub use session::{
-    BudgetInfo, SessionData, TimestampedUsage, TokenUsage, DEFAULT_TOKEN_LIMIT,
-    ROLLING_WINDOW_HOURS,
-};
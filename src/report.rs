@@ -0,0 +1,437 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::config::Config;
+use crate::format::format_tokens;
+use crate::monitor::state::AppState;
+use crate::monitor::timeseries::{self, Bucket};
+use crate::parser::TokenUsage;
+
+/// Number of projects listed per report row
+const TOP_PROJECTS: usize = 3;
+
+/// Output format of the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+/// What to report on
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub period: Bucket,
+    /// Local date or RFC 3339 time to start at (inclusive)
+    pub since: Option<String>,
+    /// Local date (inclusive) or RFC 3339 time (exclusive) to end at
+    pub until: Option<String>,
+    /// Only include projects whose path contains this
+    pub project: Option<String>,
+    pub format: Format,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectUsage {
+    pub path: String,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Usage within one report period
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRow {
+    /// Period name, e.g. "2025-01-31", "2025-W05" or "2025-01"
+    pub period: String,
+    pub start: DateTime<Utc>,
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    /// Sessions with usage in the period
+    pub sessions: u32,
+    /// Projects with the most tokens in the period, descending
+    pub top_projects: Vec<ProjectUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub period: Bucket,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub project: Option<String>,
+    pub rows: Vec<ReportRow>,
+    pub total_usage: TokenUsage,
+    pub total_cost_usd: f64,
+    pub total_sessions: u32,
+}
+
+/// Per-period accumulator
+#[derive(Default)]
+struct PeriodTotals {
+    usage: TokenUsage,
+    cost_usd: f64,
+    sessions: HashSet<String>,
+    projects: HashMap<String, (u64, f64)>,
+}
+
+/// Parse data from disk (via the usage index) and print the report
+pub async fn run(
+    config: &Config,
+    options: &ReportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let since = options
+        .since
+        .as_deref()
+        .map(timeseries::parse_time)
+        .transpose()?;
+    let until = options.until.as_deref().map(timeseries::parse_end).transpose()?;
+
+    // Leave the index to the server; anything newer is parsed into memory
+    let mut state = AppState::read_only(config);
+    state
+        .refresh()
+        .await
+        .map_err(|e| format!("Failed to read Claude data: {}", e))?;
+
+    let report = build(&state, options, since, until);
+    match options.format {
+        Format::Table => print_table(&report),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        Format::Csv => print_csv(&report),
+    }
+    Ok(())
+}
+
+/// Aggregate usage into report periods
+fn build(
+    state: &AppState,
+    options: &ReportOptions,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Report {
+    let mut periods: BTreeMap<DateTime<Utc>, PeriodTotals> = BTreeMap::new();
+    let mut all_sessions: HashSet<&str> = HashSet::new();
+
+    for (key, usages) in &state.timestamped_usages {
+        let project = state
+            .sessions
+            .get(key)
+            .map(|s| s.project_path.as_str())
+            .unwrap_or_default();
+        if let Some(filter) = &options.project {
+            if !project.contains(filter.as_str()) {
+                continue;
+            }
+        }
+
+        for tu in usages {
+            if since.map(|s| tu.timestamp < s).unwrap_or(false)
+                || until.map(|u| tu.timestamp >= u).unwrap_or(false)
+            {
+                continue;
+            }
+
+            let cost = state.pricing.cost(&tu.model, &tu.usage);
+            let totals = periods.entry(options.period.floor(tu.timestamp)).or_default();
+            totals.usage += tu.usage.clone();
+            totals.cost_usd += cost;
+            totals.sessions.insert(key.clone());
+            let project_totals = totals.projects.entry(project.to_string()).or_default();
+            project_totals.0 += tu.usage.total();
+            project_totals.1 += cost;
+            all_sessions.insert(key);
+        }
+    }
+
+    let rows: Vec<ReportRow> = periods
+        .into_iter()
+        .map(|(start, totals)| {
+            let mut projects: Vec<ProjectUsage> = totals
+                .projects
+                .into_iter()
+                .map(|(path, (tokens, cost_usd))| ProjectUsage {
+                    path,
+                    tokens,
+                    cost_usd,
+                })
+                .collect();
+            projects.sort_by_key(|p| std::cmp::Reverse(p.tokens));
+            projects.truncate(TOP_PROJECTS);
+
+            ReportRow {
+                period: options.period.label(start),
+                start,
+                usage: totals.usage,
+                cost_usd: totals.cost_usd,
+                sessions: totals.sessions.len() as u32,
+                top_projects: projects,
+            }
+        })
+        .collect();
+
+    Report {
+        period: options.period,
+        since,
+        until,
+        project: options.project.clone(),
+        total_usage: rows
+            .iter()
+            .fold(TokenUsage::default(), |acc, r| acc + r.usage.clone()),
+        total_cost_usd: rows.iter().map(|r| r.cost_usd).sum(),
+        total_sessions: all_sessions.len() as u32,
+        rows,
+    }
+}
+
+/// Last path component of a project, for compact listings
+fn short_project(path: &str) -> &str {
+    path.rsplit('/').find(|s| !s.is_empty()).unwrap_or(path)
+}
+
+fn print_table(report: &Report) {
+    if report.rows.is_empty() {
+        println!("No usage in the selected range");
+        return;
+    }
+
+    let header = [
+        "Period", "Input", "Output", "Cache W", "Cache R", "Total", "Cost", "Sessions",
+    ];
+    let mut lines: Vec<[String; 8]> = report
+        .rows
+        .iter()
+        .map(|r| table_line(r.period.clone(), &r.usage, r.cost_usd, r.sessions))
+        .collect();
+    lines.push(table_line(
+        "Total".to_string(),
+        &report.total_usage,
+        report.total_cost_usd,
+        report.total_sessions,
+    ));
+
+    let mut widths = header.map(str::len);
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if i == 0 {
+                    format!("{:<width$}", cell, width = width)
+                } else {
+                    format!("{:>width$}", cell, width = width)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let header_line = format_line(&header);
+    println!("{}  Top projects", header_line);
+    println!("{}", "-".repeat(header_line.len() + 14));
+
+    for (line, row) in lines.iter().zip(&report.rows) {
+        let projects: Vec<String> = row
+            .top_projects
+            .iter()
+            .map(|p| format!("{} ({})", short_project(&p.path), format_tokens(p.tokens)))
+            .collect();
+        println!("{}  {}", format_line(line), projects.join(", "));
+    }
+    println!("{}", "-".repeat(header_line.len() + 14));
+    println!("{}", format_line(lines.last().expect("total line pushed above")));
+}
+
+fn table_line(period: String, usage: &TokenUsage, cost_usd: f64, sessions: u32) -> [String; 8] {
+    [
+        period,
+        format_tokens(usage.input_tokens),
+        format_tokens(usage.output_tokens),
+        format_tokens(usage.cache_creation_input_tokens),
+        format_tokens(usage.cache_read_input_tokens),
+        format_tokens(usage.total()),
+        format!("${:.2}", cost_usd),
+        sessions.to_string(),
+    ]
+}
+
+fn print_csv(report: &Report) {
+    print!("{}", render_csv(report));
+}
+
+/// The report's rows as CSV, with a header line
+fn render_csv(report: &Report) -> String {
+    let mut csv = String::from(
+        "period,start,input_tokens,output_tokens,cache_creation_input_tokens,\
+         cache_read_input_tokens,total_tokens,cost_usd,sessions,top_projects\n",
+    );
+    for r in &report.rows {
+        let projects: Vec<&str> = r.top_projects.iter().map(|p| p.path.as_str()).collect();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{:.4},{},{}\n",
+            csv_field(&r.period),
+            r.start.to_rfc3339(),
+            r.usage.input_tokens,
+            r.usage.output_tokens,
+            r.usage.cache_creation_input_tokens,
+            r.usage.cache_read_input_tokens,
+            r.usage.total(),
+            r.cost_usd,
+            r.sessions,
+            csv_field(&projects.join(";")),
+        ));
+    }
+    csv
+}
+
+/// Quote a CSV field if needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{SessionData, TimestampedUsage};
+    use chrono::{Duration, Local, TimeZone};
+
+    fn options(period: Bucket, project: Option<&str>) -> ReportOptions {
+        ReportOptions {
+            period,
+            since: None,
+            until: None,
+            project: project.map(str::to_string),
+            format: Format::Csv,
+        }
+    }
+
+    /// Periods follow local time
+    fn morning() -> DateTime<Utc> {
+        Local
+            .with_ymd_and_hms(2025, 9, 1, 9, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    /// A session's project, id and `(hours after the morning, input tokens)` usages
+    type Session<'a> = (&'a str, &'a str, &'a [(i64, u64)]);
+
+    fn state(dir: &std::path::Path, sessions: &[Session]) -> AppState {
+        let config = Config {
+            index_file: dir.join("index.db"),
+            pricing_file: dir.join("pricing.json"),
+            ..Config::default()
+        };
+        let mut state = AppState::read_only(&config);
+        let start = morning();
+        for (project, id, usages) in sessions {
+            let path = dir.join(format!("{}.jsonl", id));
+            let mut session = SessionData::from_path(&path, "default");
+            session.project_path = project.to_string();
+            let key = session.key();
+            let usages = usages
+                .iter()
+                .map(|&(hours, input)| TimestampedUsage {
+                    timestamp: start + Duration::hours(hours),
+                    usage: TokenUsage {
+                        input_tokens: input,
+                        ..Default::default()
+                    },
+                    model: "claude-sonnet-4-5".to_string(),
+                    message_key: format!("{}-{}", id, hours),
+                    git_branch: None,
+                })
+                .collect();
+            state.sessions.insert(key.clone(), session);
+            state.timestamped_usages.insert(key, usages);
+        }
+        state
+    }
+
+    #[test]
+    fn usage_is_summed_per_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(
+            dir.path(),
+            &[
+                ("/src/api", "a", &[(0, 100), (1, 50), (24, 10)]),
+                ("/src/web", "b", &[(2, 1_000)]),
+                ("/src/docs", "c", &[(3, 1)]),
+                ("/src/cli", "d", &[(4, 2)]),
+            ],
+        );
+        let report = build(&state, &options(Bucket::Day, None), None, None);
+
+        assert_eq!(report.rows.len(), 2);
+        let first = &report.rows[0];
+        assert_eq!(first.usage.input_tokens, 1_153);
+        assert_eq!(first.sessions, 4);
+        let top: Vec<&str> = first.top_projects.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(top, vec!["/src/web", "/src/api", "/src/cli"]);
+        assert_eq!(report.rows[1].usage.input_tokens, 10);
+        assert_eq!(report.rows[1].sessions, 1);
+
+        assert_eq!(report.total_usage.input_tokens, 1_163);
+        assert_eq!(report.total_sessions, 4);
+        let cost: f64 = report.rows.iter().map(|r| r.cost_usd).sum();
+        assert!((report.total_cost_usd - cost).abs() < 1e-12);
+        assert!(cost > 0.0);
+    }
+
+    #[test]
+    fn ranges_and_projects_filter_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(
+            dir.path(),
+            &[
+                ("/src/api", "a", &[(0, 100), (30, 50), (60, 10)]),
+                ("/src/web", "b", &[(30, 1_000)]),
+            ],
+        );
+        let start = morning();
+        let since = Some(start + Duration::hours(1));
+        let until = Some(start + Duration::hours(60));
+
+        let report = build(&state, &options(Bucket::Month, None), since, until);
+        assert_eq!(report.total_usage.input_tokens, 1_050);
+
+        let report = build(&state, &options(Bucket::Month, Some("api")), since, until);
+        assert_eq!(report.total_usage.input_tokens, 50);
+        assert_eq!(report.total_sessions, 1);
+        assert_eq!(report.rows[0].top_projects[0].path, "/src/api");
+    }
+
+    #[test]
+    fn csv_has_a_line_per_period_and_quotes_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(
+            dir.path(),
+            &[
+                ("/src/a,b", "a", &[(0, 1_000)]),
+                ("/src/\"quoted\"", "b", &[(0, 10)]),
+            ],
+        );
+        let report = build(&state, &options(Bucket::Month, None), None, None);
+        let csv = render_csv(&report);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("period,start,input_tokens,"));
+        assert_eq!(lines[0].split(',').count(), 10);
+        let row = lines[1];
+        assert!(row.starts_with("2025-09,"), "{}", row);
+        assert!(row.contains(",1010,0,0,0,1010,"));
+        assert!(row.ends_with(",2,\"/src/a,b;/src/\"\"quoted\"\"\""), "{}", row);
+    }
+}
@@ -1,271 +1,532 @@
+use chrono::Local;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+use crate::format::{format_minutes, format_tokens};
+use crate::monitor::blocks::SessionBlock;
+use crate::monitor::detail::{BranchStats, ProjectDetail, SessionDetail, SessionTypeStats};
+use crate::monitor::history::HistoryPage;
 use crate::monitor::state::Stats;
-use crate::parser::SessionData;
-
-/// Format token count with K/M suffix
-fn format_tokens(count: u64) -> String {
-    if count >= 1_000_000 {
-        format!("{:.1}M", count as f64 / 1_000_000.0)
-    } else if count >= 1_000 {
-        format!("{:.1}K", count as f64 / 1_000.0)
+use crate::monitor::timeseries::TimeBucket;
+use crate::monitor::tools::ToolSummary;
+use crate::parser::{model_family, SessionData};
+
+/// Number of most recent blocks shown in the dashboard timeline
+pub const TIMELINE_BLOCKS: usize = 12;
+
+/// Number of most called tools shown on the dashboard
+const DASHBOARD_TOOLS: usize = 15;
+
+/// Format a duration in seconds as "1h 5m" or "42s"
+fn format_duration(secs: i64) -> String {
+    if secs >= 60 {
+        format_minutes(secs / 60)
     } else {
-        count.to_string()
+        format!("{}s", secs)
     }
 }
 
-/// Render the main index page
-pub fn render_index(stats: &Stats, active_sessions: &[&SessionData]) -> String {
+/// Encode a value for use as one URL path segment
+fn path_segment(value: &str) -> String {
+    utf8_percent_encode(value, NON_ALPHANUMERIC).to_string()
+}
+
+/// Link to a project's detail page
+fn project_url(path: &str) -> String {
+    format!("/projects/{}", path_segment(path))
+}
+
+/// Link to a session's detail page
+fn session_url(session: &SessionData) -> String {
+    session_link(&session.root, &session.project_path, &session.session_id)
+}
+
+/// Link to the detail page of the session `id` of a project in data root `root`
+fn session_link(root: &str, project: &str, id: &str) -> String {
+    format!(
+        "/sessions/{}/{}?root={}",
+        path_segment(project),
+        path_segment(id),
+        path_segment(root),
+    )
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// CSS class of a progress bar at the given percentage
+fn progress_class(percentage: f64) -> &'static str {
+    if percentage < 50.0 {
+        "low"
+    } else if percentage < 75.0 {
+        "medium"
+    } else if percentage < 90.0 {
+        "high"
+    } else {
+        "critical"
+    }
+}
+
+/// Format a dollar amount
+fn format_cost(usd: f64) -> String {
+    if usd >= 1_000.0 {
+        format!("${:.1}K", usd / 1_000.0)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+/// Styles shared by all pages
+const STYLES: &str = r#"
+* { box-sizing: border-box; margin: 0; padding: 0; }
+body {
+    font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+    background: #0f172a;
+    color: #e2e8f0;
+    min-height: 100vh;
+    padding: 2rem;
+}
+.container { max-width: 1200px; margin: 0 auto; }
+h1 {
+    font-size: 2rem;
+    margin-bottom: 2rem;
+    color: #f8fafc;
+    display: flex;
+    align-items: center;
+    gap: 0.75rem;
+}
+h1::before {
+    content: '';
+    display: inline-block;
+    width: 12px;
+    height: 12px;
+    background: #22c55e;
+    border-radius: 50%;
+    animation: pulse 2s infinite;
+}
+@keyframes pulse {
+    0%, 100% { opacity: 1; }
+    50% { opacity: 0.5; }
+}
+.budget-section {
+    background: linear-gradient(135deg, #1e293b 0%, #0f172a 100%);
+    border-radius: 16px;
+    padding: 1.5rem;
+    margin-bottom: 2rem;
+    border: 1px solid #334155;
+}
+.budget-header {
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+    margin-bottom: 1rem;
+}
+.budget-title {
+    font-size: 1.25rem;
+    color: #f8fafc;
+    display: flex;
+    align-items: center;
+    gap: 0.5rem;
+}
+.budget-percentage {
+    font-size: 2.5rem;
+    font-weight: 700;
+    color: #818cf8;
+}
+.progress-container {
+    background: #0f172a;
+    border-radius: 8px;
+    height: 24px;
+    overflow: hidden;
+    margin-bottom: 1rem;
+}
+.progress-bar {
+    height: 100%;
+    border-radius: 8px;
+    transition: width 0.5s ease;
+}
+.progress-bar.low { background: linear-gradient(90deg, #22c55e, #4ade80); }
+.progress-bar.medium { background: linear-gradient(90deg, #facc15, #fde047); }
+.progress-bar.high { background: linear-gradient(90deg, #f97316, #fb923c); }
+.progress-bar.critical { background: linear-gradient(90deg, #ef4444, #f87171); }
+.budget-stats {
+    display: flex;
+    justify-content: space-between;
+    flex-wrap: wrap;
+    gap: 1rem;
+}
+.budget-stat {
+    display: flex;
+    flex-direction: column;
+    gap: 0.25rem;
+}
+.budget-stat-label {
+    font-size: 0.75rem;
+    color: #94a3b8;
+    text-transform: uppercase;
+    letter-spacing: 0.05em;
+}
+.budget-stat-value {
+    font-size: 1.25rem;
+    font-weight: 600;
+    color: #f8fafc;
+}
+.budget-stat-value.remaining { color: #22c55e; }
+.budget-projection {
+    margin-top: 1rem;
+    font-size: 0.875rem;
+    color: #94a3b8;
+}
+.budget-projection.warning { color: #f97316; }
+.block-list { list-style: none; }
+.block-item {
+    display: grid;
+    grid-template-columns: 12rem 1fr 14rem;
+    gap: 1rem;
+    align-items: center;
+    padding: 0.5rem 1rem;
+    background: #0f172a;
+    border-radius: 8px;
+    margin-bottom: 0.5rem;
+}
+.block-item.active { border: 1px solid #22c55e; }
+.block-time { font-family: monospace; font-size: 0.875rem; color: #e2e8f0; }
+.block-item .progress-container { height: 12px; margin-bottom: 0; }
+.block-stats {
+    display: flex;
+    justify-content: flex-end;
+    gap: 1rem;
+    font-size: 0.875rem;
+    color: #94a3b8;
+}
+.stats-grid {
+    display: grid;
+    grid-template-columns: repeat(auto-fit, minmax(200px, 1fr));
+    gap: 1rem;
+    margin-bottom: 2rem;
+}
+.stat-card {
+    background: #1e293b;
+    border-radius: 12px;
+    padding: 1.5rem;
+    border: 1px solid #334155;
+}
+.stat-label {
+    font-size: 0.875rem;
+    color: #94a3b8;
+    margin-bottom: 0.5rem;
+}
+.stat-value {
+    font-size: 2rem;
+    font-weight: 700;
+    color: #f8fafc;
+}
+.stat-value.highlight { color: #818cf8; }
+.stat-value.green { color: #22c55e; }
+.stat-value.yellow { color: #facc15; }
+.section {
+    background: #1e293b;
+    border-radius: 12px;
+    padding: 1.5rem;
+    margin-bottom: 1.5rem;
+    border: 1px solid #334155;
+}
+.section-title {
+    font-size: 1.25rem;
+    margin-bottom: 1rem;
+    color: #f8fafc;
+}
+.session-list { list-style: none; }
+.session-item {
+    padding: 0.75rem 1rem;
+    background: #0f172a;
+    border-radius: 8px;
+    margin-bottom: 0.5rem;
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+}
+.session-item:last-child { margin-bottom: 0; }
+.session-info { display: flex; flex-direction: column; gap: 0.25rem; }
+.session-project { font-weight: 500; color: #e2e8f0; }
+.session-id { font-size: 0.75rem; color: #64748b; font-family: monospace; }
+.session-stats {
+    display: flex;
+    gap: 1rem;
+    font-size: 0.875rem;
+    color: #94a3b8;
+}
+.badge {
+    font-size: 0.75rem;
+    padding: 0.25rem 0.5rem;
+    border-radius: 4px;
+    background: #4f46e5;
+    color: white;
+}
+.badge.agent { background: #7c3aed; }
+.badge.root { background: #334155; color: #cbd5e1; }
+.badge.opus { background: #c2410c; }
+.badge.sonnet { background: #2563eb; }
+.badge.haiku { background: #059669; }
+.badge.other { background: #475569; }
+.model-list { list-style: none; }
+.model-item {
+    padding: 0.75rem 1rem;
+    background: #0f172a;
+    border-radius: 8px;
+    margin-bottom: 0.5rem;
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+}
+.model-info { display: flex; align-items: center; gap: 0.75rem; }
+.model-name { font-family: monospace; color: #e2e8f0; }
+.model-stats { display: flex; gap: 1.5rem; font-size: 0.875rem; color: #94a3b8; }
+.project-list { list-style: none; }
+.project-item {
+    padding: 0.75rem 1rem;
+    background: #0f172a;
+    border-radius: 8px;
+    margin-bottom: 0.5rem;
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+}
+.project-path { font-family: monospace; color: #e2e8f0; }
+.project-stats { display: flex; gap: 1.5rem; font-size: 0.875rem; color: #94a3b8; }
+.empty { color: #64748b; font-style: italic; padding: 1rem; text-align: center; }
+.refresh-btn {
+    background: #3b82f6;
+    color: white;
+    border: none;
+    padding: 0.5rem 1rem;
+    border-radius: 6px;
+    cursor: pointer;
+    font-size: 0.875rem;
+}
+.refresh-btn:hover { background: #2563eb; }
+.header {
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+    margin-bottom: 2rem;
+}
+a.item-link { color: inherit; text-decoration: none; }
+a.item-link:hover, a.item-link:hover span:first-child { color: #818cf8; }
+.back-link { color: #818cf8; text-decoration: none; font-size: 0.875rem; }
+.back-link:hover { text-decoration: underline; }
+.data-table { width: 100%; border-collapse: collapse; font-size: 0.875rem; }
+.data-table th {
+    text-align: right;
+    color: #94a3b8;
+    font-weight: 500;
+    padding: 0.5rem;
+    border-bottom: 1px solid #334155;
+}
+.data-table td {
+    text-align: right;
+    padding: 0.4rem 0.5rem;
+    border-bottom: 1px solid #1e293b;
+    font-family: monospace;
+}
+.data-table th.text, .data-table td.text { text-align: left; }
+.data-table tr.user td { color: #64748b; }
+.chart {
+    display: flex;
+    align-items: flex-end;
+    gap: 3px;
+    height: 160px;
+    padding-bottom: 0.25rem;
+    border-bottom: 1px solid #334155;
+}
+.chart-bar {
+    flex: 1;
+    height: 100%;
+    display: flex;
+    flex-direction: column;
+    justify-content: flex-end;
+}
+.chart-segment { background: #4f46e5; border-radius: 2px 2px 0 0; }
+.chart-segment.agent { background: #7c3aed; border-radius: 0; }
+.chart-axis {
+    display: flex;
+    justify-content: space-between;
+    margin-top: 0.5rem;
+    font-size: 0.75rem;
+    color: #64748b;
+}
+.chart-legend { display: flex; gap: 1rem; margin-top: 0.5rem; font-size: 0.75rem; color: #94a3b8; }
+.chart-legend span::before {
+    content: '';
+    display: inline-block;
+    width: 10px;
+    height: 10px;
+    margin-right: 0.35rem;
+    border-radius: 2px;
+    background: #4f46e5;
+}
+.chart-legend span.agent::before { background: #7c3aed; }
+.header-actions { display: flex; align-items: center; gap: 1rem; }
+.search-form { display: flex; flex-wrap: wrap; gap: 0.75rem; margin-bottom: 1.5rem; }
+.search-form input, .search-form select {
+    background: #0f172a;
+    color: #e2e8f0;
+    border: 1px solid #334155;
+    border-radius: 6px;
+    padding: 0.5rem 0.75rem;
+    font-size: 0.875rem;
+}
+.search-form input[type="search"] { flex: 1; min-width: 16rem; }
+.prompt-list { list-style: none; }
+.prompt-item {
+    padding: 0.75rem 1rem;
+    background: #0f172a;
+    border-radius: 8px;
+    margin-bottom: 0.5rem;
+}
+.prompt-text {
+    white-space: pre-wrap;
+    word-break: break-word;
+    color: #e2e8f0;
+    max-height: 12rem;
+    overflow: auto;
+}
+.prompt-meta {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 1rem;
+    margin-top: 0.5rem;
+    font-size: 0.75rem;
+    color: #64748b;
+}
+.prompt-meta a { color: #818cf8; text-decoration: none; }
+.pager { display: flex; justify-content: space-between; align-items: center; margin-top: 1rem; }
+"#;
+
+/// Wrap page content in the HTML document shared by all pages
+fn render_page(title: &str, body_attrs: &str, content: &str) -> String {
     format!(
         r#"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Claude Monitor</title>
+    <title>{title}</title>
     <script src="https://unpkg.com/htmx.org@1.9.10"></script>
-    <style>
-        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
-        body {{
-            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
-            background: #0f172a;
-            color: #e2e8f0;
-            min-height: 100vh;
-            padding: 2rem;
-        }}
-        .container {{ max-width: 1200px; margin: 0 auto; }}
-        h1 {{
-            font-size: 2rem;
-            margin-bottom: 2rem;
-            color: #f8fafc;
-            display: flex;
-            align-items: center;
-            gap: 0.75rem;
-        }}
-        h1::before {{
-            content: '';
-            display: inline-block;
-            width: 12px;
-            height: 12px;
-            background: #22c55e;
-            border-radius: 50%;
-            animation: pulse 2s infinite;
-        }}
-        @keyframes pulse {{
-            0%, 100% {{ opacity: 1; }}
-            50% {{ opacity: 0.5; }}
-        }}
-        .budget-section {{
-            background: linear-gradient(135deg, #1e293b 0%, #0f172a 100%);
-            border-radius: 16px;
-            padding: 1.5rem;
-            margin-bottom: 2rem;
-            border: 1px solid #334155;
-        }}
-        .budget-header {{
-            display: flex;
-            justify-content: space-between;
-            align-items: center;
-            margin-bottom: 1rem;
-        }}
-        .budget-title {{
-            font-size: 1.25rem;
-            color: #f8fafc;
-            display: flex;
-            align-items: center;
-            gap: 0.5rem;
-        }}
-        .budget-percentage {{
-            font-size: 2.5rem;
-            font-weight: 700;
-            color: #818cf8;
-        }}
-        .progress-container {{
-            background: #0f172a;
-            border-radius: 8px;
-            height: 24px;
-            overflow: hidden;
-            margin-bottom: 1rem;
-        }}
-        .progress-bar {{
-            height: 100%;
-            border-radius: 8px;
-            transition: width 0.5s ease;
-        }}
-        .progress-bar.low {{ background: linear-gradient(90deg, #22c55e, #4ade80); }}
-        .progress-bar.medium {{ background: linear-gradient(90deg, #facc15, #fde047); }}
-        .progress-bar.high {{ background: linear-gradient(90deg, #f97316, #fb923c); }}
-        .progress-bar.critical {{ background: linear-gradient(90deg, #ef4444, #f87171); }}
-        .budget-stats {{
-            display: flex;
-            justify-content: space-between;
-            flex-wrap: wrap;
-            gap: 1rem;
-        }}
-        .budget-stat {{
-            display: flex;
-            flex-direction: column;
-            gap: 0.25rem;
-        }}
-        .budget-stat-label {{
-            font-size: 0.75rem;
-            color: #94a3b8;
-            text-transform: uppercase;
-            letter-spacing: 0.05em;
-        }}
-        .budget-stat-value {{
-            font-size: 1.25rem;
-            font-weight: 600;
-            color: #f8fafc;
-        }}
-        .budget-stat-value.remaining {{ color: #22c55e; }}
-        .stats-grid {{
-            display: grid;
-            grid-template-columns: repeat(auto-fit, minmax(200px, 1fr));
-            gap: 1rem;
-            margin-bottom: 2rem;
-        }}
-        .stat-card {{
-            background: #1e293b;
-            border-radius: 12px;
-            padding: 1.5rem;
-            border: 1px solid #334155;
-        }}
-        .stat-label {{
-            font-size: 0.875rem;
-            color: #94a3b8;
-            margin-bottom: 0.5rem;
-        }}
-        .stat-value {{
-            font-size: 2rem;
-            font-weight: 700;
-            color: #f8fafc;
-        }}
-        .stat-value.highlight {{ color: #818cf8; }}
-        .stat-value.green {{ color: #22c55e; }}
-        .stat-value.yellow {{ color: #facc15; }}
-        .section {{
-            background: #1e293b;
-            border-radius: 12px;
-            padding: 1.5rem;
-            margin-bottom: 1.5rem;
-            border: 1px solid #334155;
-        }}
-        .section-title {{
-            font-size: 1.25rem;
-            margin-bottom: 1rem;
-            color: #f8fafc;
-        }}
-        .session-list {{ list-style: none; }}
-        .session-item {{
-            padding: 0.75rem 1rem;
-            background: #0f172a;
-            border-radius: 8px;
-            margin-bottom: 0.5rem;
-            display: flex;
-            justify-content: space-between;
-            align-items: center;
-        }}
-        .session-item:last-child {{ margin-bottom: 0; }}
-        .session-info {{ display: flex; flex-direction: column; gap: 0.25rem; }}
-        .session-project {{ font-weight: 500; color: #e2e8f0; }}
-        .session-id {{ font-size: 0.75rem; color: #64748b; font-family: monospace; }}
-        .session-stats {{
-            display: flex;
-            gap: 1rem;
-            font-size: 0.875rem;
-            color: #94a3b8;
-        }}
-        .badge {{
-            font-size: 0.75rem;
-            padding: 0.25rem 0.5rem;
-            border-radius: 4px;
-            background: #4f46e5;
-            color: white;
-        }}
-        .badge.agent {{ background: #7c3aed; }}
-        .project-list {{ list-style: none; }}
-        .project-item {{
-            padding: 0.75rem 1rem;
-            background: #0f172a;
-            border-radius: 8px;
-            margin-bottom: 0.5rem;
-            display: flex;
-            justify-content: space-between;
-            align-items: center;
-        }}
-        .project-path {{ font-family: monospace; color: #e2e8f0; }}
-        .project-stats {{ display: flex; gap: 1.5rem; font-size: 0.875rem; color: #94a3b8; }}
-        .empty {{ color: #64748b; font-style: italic; padding: 1rem; text-align: center; }}
-        .refresh-btn {{
-            background: #3b82f6;
-            color: white;
-            border: none;
-            padding: 0.5rem 1rem;
-            border-radius: 6px;
-            cursor: pointer;
-            font-size: 0.875rem;
-        }}
-        .refresh-btn:hover {{ background: #2563eb; }}
-        .header {{
-            display: flex;
-            justify-content: space-between;
-            align-items: center;
-            margin-bottom: 2rem;
-        }}
-    </style>
+    <script src="https://unpkg.com/htmx.org@1.9.10/dist/ext/sse.js"></script>
+    <style>{styles}</style>
 </head>
-<body>
-    <div class="container">
+<body {body_attrs}>
+{content}
+</body>
+</html>"#,
+        title = title,
+        styles = STYLES,
+        body_attrs = body_attrs,
+        content = content,
+    )
+}
+
+/// Render the main index page
+pub fn render_index(
+    stats: &Stats,
+    active_sessions: &[&SessionData],
+    blocks: &[SessionBlock],
+    tools: &[ToolSummary],
+) -> String {
+    let content = format!(
+        r#"    <div class="container">
         <div class="header">
             <h1>Claude Monitor</h1>
-            <button class="refresh-btn" hx-get="/api/refresh" hx-swap="none" hx-on::after-request="htmx.trigger('#budget-container', 'refresh'); htmx.trigger('#stats-container', 'refresh'); htmx.trigger('#sessions-container', 'refresh');">
+            <div class="header-actions">
+            <a class="back-link" href="/history">Prompt History</a>
+            <button class="refresh-btn" hx-get="/api/refresh" hx-swap="none" hx-on::after-request="htmx.trigger('#budget-container', 'refresh'); htmx.trigger('#stats-container', 'refresh'); htmx.trigger('#sessions-container', 'refresh'); htmx.trigger('#models-container', 'refresh'); htmx.trigger('#blocks-container', 'refresh'); htmx.trigger('#tools-container', 'refresh');">
                 Refresh
             </button>
+            </div>
         </div>
 
-        <div id="budget-container" hx-get="/partials/budget" hx-trigger="load, refresh, every 10s" hx-swap="innerHTML">
+        <div id="budget-container" hx-get="/partials/budget" hx-trigger="refresh, sse:stats, every 60s" hx-swap="innerHTML">
             {budget_html}
         </div>
 
-        <div id="stats-container" hx-get="/partials/stats" hx-trigger="load, refresh, every 10s" hx-swap="innerHTML">
+        <div id="stats-container" hx-get="/partials/stats" hx-trigger="refresh, sse:stats, every 60s" hx-swap="innerHTML">
             {stats_html}
         </div>
 
-        <div id="sessions-container" hx-get="/partials/sessions" hx-trigger="load, refresh, every 10s" hx-swap="innerHTML">
+        <div id="sessions-container" hx-get="/partials/sessions" hx-trigger="refresh, sse:stats, every 60s" hx-swap="innerHTML">
             {sessions_html}
         </div>
 
+        <div id="models-container" hx-get="/partials/models" hx-trigger="refresh, sse:stats, every 60s" hx-swap="innerHTML">
+            {models_html}
+        </div>
+
+        <div id="blocks-container" hx-get="/partials/blocks" hx-trigger="refresh, sse:stats, every 60s" hx-swap="innerHTML">
+            {blocks_html}
+        </div>
+
+        <div id="tools-container" hx-get="/partials/tools" hx-trigger="refresh, sse:stats, every 60s" hx-swap="innerHTML">
+            {tools_html}
+        </div>
+
+        {roots_html}
+
         <div class="section">
             <h2 class="section-title">Projects by Usage</h2>
             {projects_html}
         </div>
-    </div>
-</body>
-</html>"#,
+    </div>"#,
         budget_html = render_budget_partial(stats),
         stats_html = render_stats_partial(stats),
-        sessions_html = render_sessions_partial(active_sessions),
+        sessions_html = render_sessions_partial(active_sessions, stats.roots.len() > 1),
+        models_html = render_models_partial(stats),
+        blocks_html = render_blocks_partial(blocks, stats.budget.limit),
+        tools_html = render_tools_partial(tools),
+        roots_html = render_roots_list(stats),
         projects_html = render_projects_list(stats),
-    )
+    );
+
+    render_page("Claude Monitor", r#"hx-ext="sse" sse-connect="/api/events""#, &content)
 }
 
 /// Render budget section partial
 pub fn render_budget_partial(stats: &Stats) -> String {
     let percentage = stats.budget.percentage;
-    let progress_class = if percentage < 50.0 {
-        "low"
-    } else if percentage < 75.0 {
-        "medium"
-    } else if percentage < 90.0 {
-        "high"
-    } else {
-        "critical"
+    let resets_in = stats
+        .budget
+        .reset_minutes
+        .map(format_minutes)
+        .unwrap_or_else(|| "-".to_string());
+    let block_cost = stats
+        .current_block
+        .as_ref()
+        .map(|b| b.cost_usd)
+        .unwrap_or(0.0);
+
+    let burn = &stats.burn_rate;
+    let projection = match (burn.exhaustion_time, burn.minutes_to_exhaustion) {
+        (Some(at), Some(minutes)) => format!(
+            r#"<div class="budget-projection warning">At this pace the budget runs out in {} ({}), before the block resets</div>"#,
+            format_minutes(minutes),
+            at.with_timezone(&Local).format("%H:%M"),
+        ),
+        _ if burn.tokens_per_minute > 0.0 => {
+            r#"<div class="budget-projection">On pace to last until the block resets</div>"#
+                .to_string()
+        }
+        _ => String::new(),
     };
 
     format!(
         r#"<div class="budget-section">
     <div class="budget-header">
         <div class="budget-title">
-            <span>5-Hour Rolling Budget</span>
+            <span>Current {window_hours}-Hour Block</span>
         </div>
         <div class="budget-percentage">{percentage:.1}%</div>
     </div>
@@ -285,13 +546,97 @@ pub fn render_budget_partial(stats: &Stats) -> String {
             <span class="budget-stat-label">Remaining</span>
             <span class="budget-stat-value remaining">{remaining}</span>
         </div>
+        <div class="budget-stat">
+            <span class="budget-stat-label">Resets In</span>
+            <span class="budget-stat-value">{resets_in}</span>
+        </div>
+        <div class="budget-stat">
+            <span class="budget-stat-label">Burn Rate</span>
+            <span class="budget-stat-value">{burn_rate}/min</span>
+        </div>
+        <div class="budget-stat">
+            <span class="budget-stat-label">Block Cost</span>
+            <span class="budget-stat-value">{block_cost}</span>
+        </div>
+        <div class="budget-stat">
+            <span class="budget-stat-label">Lifetime Cost</span>
+            <span class="budget-stat-value">{lifetime_cost}</span>
+        </div>
     </div>
+    {projection}
 </div>"#,
         percentage = percentage,
-        progress_class = progress_class,
+        progress_class = progress_class(percentage),
         used = format_tokens(stats.budget.used),
         limit = format_tokens(stats.budget.limit),
         remaining = format_tokens(stats.budget.remaining),
+        window_hours = stats.budget.window_hours,
+        resets_in = resets_in,
+        burn_rate = format_tokens(burn.tokens_per_minute.round() as u64),
+        projection = projection,
+        block_cost = format_cost(block_cost),
+        lifetime_cost = format_cost(stats.total_cost_usd),
+    )
+}
+
+/// Render the usage block timeline partial, most recent block first
+pub fn render_blocks_partial(blocks: &[SessionBlock], limit: u64) -> String {
+    if blocks.is_empty() {
+        return r#"<div class="section">
+            <h2 class="section-title">Usage Blocks</h2>
+            <div class="empty">No usage recorded</div>
+        </div>"#
+            .to_string();
+    }
+
+    let items: Vec<String> = blocks
+        .iter()
+        .rev()
+        .take(TIMELINE_BLOCKS)
+        .map(|b| {
+            let used = b.usage.billable();
+            let percentage = if limit > 0 {
+                used as f64 / limit as f64 * 100.0
+            } else {
+                0.0
+            };
+
+            format!(
+                r#"<li class="block-item{active_class}">
+                <span class="block-time">{start} – {end}</span>
+                <div class="progress-container">
+                    <div class="progress-bar {progress_class}" style="width: {width:.1}%;"></div>
+                </div>
+                <div class="block-stats">
+                    <span>{tokens} tokens</span>
+                    <span>{cost}</span>
+                    {badge}
+                </div>
+            </li>"#,
+                active_class = if b.is_active { " active" } else { "" },
+                start = b.start.with_timezone(&Local).format("%b %d %H:%M"),
+                end = b.end.with_timezone(&Local).format("%H:%M"),
+                progress_class = progress_class(percentage),
+                width = percentage.min(100.0),
+                tokens = format_tokens(used),
+                cost = format_cost(b.cost_usd),
+                badge = if b.is_active {
+                    r#"<span class="badge">Active</span>"#
+                } else {
+                    ""
+                },
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<div class="section">
+        <h2 class="section-title">Usage Blocks</h2>
+        <ul class="block-list">
+            {items}
+        </ul>
+    </div>"#,
+        items = items.join("\n")
     )
 }
 
@@ -345,8 +690,8 @@ pub fn render_stats_partial(stats: &Stats) -> String {
     )
 }
 
-/// Render active sessions list partial
-pub fn render_sessions_partial(sessions: &[&SessionData]) -> String {
+/// Render active sessions list partial; `show_roots` labels each session with its data root
+pub fn render_sessions_partial(sessions: &[&SessionData], show_roots: bool) -> String {
     if sessions.is_empty() {
         return r#"<div class="section">
             <h2 class="section-title">Active Sessions</h2>
@@ -355,6 +700,20 @@ pub fn render_sessions_partial(sessions: &[&SessionData]) -> String {
             .to_string();
     }
 
+    format!(
+        r#"<div class="section">
+        <h2 class="section-title">Active Sessions ({count})</h2>
+        <ul class="session-list">
+            {items}
+        </ul>
+    </div>"#,
+        count = sessions.len(),
+        items = render_session_items(sessions, show_roots)
+    )
+}
+
+/// Session list items linking to each session's detail page
+fn render_session_items(sessions: &[&SessionData], show_roots: bool) -> String {
     let items: Vec<String> = sessions
         .iter()
         .map(|s| {
@@ -363,36 +722,137 @@ pub fn render_sessions_partial(sessions: &[&SessionData]) -> String {
             } else {
                 r#"<span class="badge">Session</span>"#
             };
+            let root_badge = if show_roots {
+                format!(r#"<span class="badge root">{}</span>"#, s.root)
+            } else {
+                String::new()
+            };
 
             format!(
                 r#"<li class="session-item">
-                <div class="session-info">
+                <a class="session-info item-link" href="{url}">
                     <span class="session-project">{project}</span>
                     <span class="session-id">{session_id}</span>
-                </div>
+                </a>
                 <div class="session-stats">
+                    <span>{last_activity}</span>
                     <span>{messages} msgs</span>
                     <span>{tokens} tokens</span>
+                    <span>{cost}</span>
+                    {root_badge}
                     {badge}
                 </div>
             </li>"#,
-                project = s.project_path,
-                session_id = &s.session_id[..8.min(s.session_id.len())],
+                url = session_url(s),
+                project = escape_html(&s.project_path),
+                session_id = escape_html(&s.session_id[..8.min(s.session_id.len())]),
+                last_activity = s
+                    .last_activity
+                    .map(|t| t.with_timezone(&Local).format("%b %d %H:%M").to_string())
+                    .unwrap_or_default(),
                 messages = s.message_count,
                 tokens = format_tokens(s.usage.total()),
+                cost = format_cost(s.cost_usd),
+                root_badge = root_badge,
                 badge = badge,
             )
         })
         .collect();
 
+    items.join("\n")
+}
+
+/// Render per-model usage partial
+pub fn render_models_partial(stats: &Stats) -> String {
+    if stats.models.is_empty() {
+        return r#"<div class="section">
+            <h2 class="section-title">Usage by Model</h2>
+            <div class="empty">No model usage recorded</div>
+        </div>"#
+            .to_string();
+    }
+
+    let lifetime_total = stats.total_usage.total().max(1);
+
+    let items: Vec<String> = stats
+        .models
+        .iter()
+        .map(|m| {
+            format!(
+                r#"<li class="model-item">
+                <div class="model-info">
+                    <span class="badge {family_class}">{family}</span>
+                    <span class="model-name">{model}</span>
+                </div>
+                <div class="model-stats">
+                    <span>{rolling} tokens ({window_hours}h)</span>
+                    <span>{tokens} tokens</span>
+                    <span>{cost}</span>
+                    <span>{share:.1}%</span>
+                </div>
+            </li>"#,
+                family_class = m.family.to_ascii_lowercase(),
+                family = m.family,
+                model = escape_html(&m.model),
+                window_hours = stats.budget.window_hours,
+                rolling = format_tokens(m.rolling_usage.total()),
+                tokens = format_tokens(m.usage.total()),
+                cost = format_cost(m.cost_usd),
+                share = m.usage.total() as f64 / lifetime_total as f64 * 100.0,
+            )
+        })
+        .collect();
+
     format!(
         r#"<div class="section">
-        <h2 class="section-title">Active Sessions ({count})</h2>
-        <ul class="session-list">
+        <h2 class="section-title">Usage by Model</h2>
+        <ul class="model-list">
             {items}
         </ul>
     </div>"#,
-        count = sessions.len(),
+        items = items.join("\n")
+    )
+}
+
+/// Render per-data-root usage; omitted when only one root is monitored
+fn render_roots_list(stats: &Stats) -> String {
+    if stats.roots.len() < 2 {
+        return String::new();
+    }
+
+    let items: Vec<String> = stats
+        .roots
+        .iter()
+        .map(|r| {
+            format!(
+                r#"<li class="project-item">
+                <div class="model-info">
+                    <span class="badge root">{label}</span>
+                    <span class="project-path">{path}</span>
+                </div>
+                <div class="project-stats">
+                    <span>{sessions} sessions</span>
+                    <span>{rolling} tokens ({window_hours}h)</span>
+                    <span>{tokens} tokens</span>
+                    <span>{cost}</span>
+                </div>
+            </li>"#,
+                label = r.label,
+                path = r.path.display(),
+                sessions = r.session_count,
+                window_hours = stats.budget.window_hours,
+                rolling = format_tokens(r.rolling_usage.total()),
+                tokens = format_tokens(r.usage.total()),
+                cost = format_cost(r.cost_usd),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<div class="section">
+            <h2 class="section-title">Usage by Data Root</h2>
+            <ul class="project-list">{items}</ul>
+        </div>"#,
         items = items.join("\n")
     )
 }
@@ -409,17 +869,20 @@ fn render_projects_list(stats: &Stats) -> String {
         .map(|p| {
             format!(
                 r#"<li class="project-item">
-                <span class="project-path">{path}</span>
+                <a class="project-path item-link" href="{url}">{path}</a>
                 <div class="project-stats">
                     <span>{sessions} sessions</span>
                     <span>{messages} msgs</span>
                     <span>{tokens} tokens</span>
+                    <span>{cost}</span>
                 </div>
             </li>"#,
-                path = p.path,
+                url = project_url(&p.path),
+                path = escape_html(&p.path),
                 sessions = p.session_count,
                 messages = p.message_count,
                 tokens = format_tokens(p.usage.total()),
+                cost = format_cost(p.cost_usd),
             )
         })
         .collect();
@@ -429,3 +892,621 @@ fn render_projects_list(stats: &Stats) -> String {
         items = items.join("\n")
     )
 }
+
+/// Render the drill-down page of one session
+pub fn render_session_page(detail: &SessionDetail) -> String {
+    let session = &detail.session;
+    let format_time = |ts: Option<chrono::DateTime<chrono::Utc>>| {
+        ts.map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let models: Vec<String> = session
+        .models
+        .keys()
+        .map(|model| {
+            format!(
+                r#"<span class="badge {class}">{model}</span>"#,
+                class = model_family(model).to_ascii_lowercase(),
+                model = escape_html(model),
+            )
+        })
+        .collect();
+
+    let rows: Vec<String> = detail
+        .messages
+        .iter()
+        .map(|m| {
+            let usage = m.usage.clone().unwrap_or_default();
+            let tokens = |count: u64| {
+                if m.usage.is_some() {
+                    format_tokens(count)
+                } else {
+                    "-".to_string()
+                }
+            };
+            format!(
+                r#"<tr class="{role}">
+                <td class="text">{time}</td>
+                <td class="text">{role}</td>
+                <td class="text">{model}</td>
+                <td>{input}</td>
+                <td>{output}</td>
+                <td>{cache_write}</td>
+                <td>{cache_read}</td>
+                <td>{cumulative}</td>
+                <td>{cost}</td>
+            </tr>"#,
+                role = m.role,
+                time = format_time(m.timestamp),
+                model = escape_html(m.model.as_deref().unwrap_or("-")),
+                input = tokens(usage.input_tokens),
+                output = tokens(usage.output_tokens),
+                cache_write = tokens(usage.cache_creation_input_tokens),
+                cache_read = tokens(usage.cache_read_input_tokens),
+                cumulative = format_tokens(m.cumulative.total()),
+                cost = if m.usage.is_some() {
+                    format_cost(m.cost_usd)
+                } else {
+                    "-".to_string()
+                },
+            )
+        })
+        .collect();
+
+    let timeline = if rows.is_empty() {
+        r#"<div class="empty">No messages</div>"#.to_string()
+    } else {
+        format!(
+            r#"<table class="data-table">
+            <tr>
+                <th class="text">Time</th><th class="text">Role</th><th class="text">Model</th>
+                <th>Input</th><th>Output</th><th>Cache W</th><th>Cache R</th><th>Cumulative</th><th>Cost</th>
+            </tr>
+            {rows}
+        </table>"#,
+            rows = rows.join("\n")
+        )
+    };
+
+    let note = if detail.from_index {
+        r#"<div class="budget-projection">The transcript no longer exists; only indexed usage is shown.</div>"#
+    } else {
+        ""
+    };
+
+    let content = format!(
+        r#"    <div class="container">
+        <div class="header">
+            <h1>{kind} {session_id}</h1>
+            <a class="back-link" href="/">&larr; Dashboard</a>
+        </div>
+
+        <div class="section">
+            <h2 class="section-title"><a class="item-link" href="{project_url}">{project}</a></h2>
+            <div class="budget-stats">
+                <div class="budget-stat">
+                    <span class="budget-stat-label">Started</span>
+                    <span class="budget-stat-value">{started}</span>
+                </div>
+                <div class="budget-stat">
+                    <span class="budget-stat-label">Duration</span>
+                    <span class="budget-stat-value">{duration}</span>
+                </div>
+                <div class="budget-stat">
+                    <span class="budget-stat-label">Messages</span>
+                    <span class="budget-stat-value">{messages}</span>
+                </div>
+                <div class="budget-stat">
+                    <span class="budget-stat-label">Total Tokens</span>
+                    <span class="budget-stat-value">{tokens}</span>
+                </div>
+                <div class="budget-stat">
+                    <span class="budget-stat-label">Cache Hit Ratio</span>
+                    <span class="budget-stat-value">{cache_hit:.1}%</span>
+                </div>
+                <div class="budget-stat">
+                    <span class="budget-stat-label">Cost</span>
+                    <span class="budget-stat-value">{cost}</span>
+                </div>
+            </div>
+            <div class="budget-projection">Models: {models} &middot; Branch: {branch} &middot; Root: {root}</div>
+            {note}
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Tools</h2>
+            {tools}
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Timeline</h2>
+            {timeline}
+        </div>
+    </div>"#,
+        kind = if session.is_agent { "Agent" } else { "Session" },
+        session_id = escape_html(&session.session_id[..8.min(session.session_id.len())]),
+        project_url = project_url(&session.project_path),
+        project = escape_html(&session.project_path),
+        started = format_time(detail.started_at),
+        duration = detail
+            .duration_secs
+            .map(format_duration)
+            .unwrap_or_else(|| "-".to_string()),
+        messages = detail.messages.len(),
+        tokens = format_tokens(session.usage.total()),
+        cache_hit = detail.cache_hit_ratio * 100.0,
+        cost = format_cost(session.cost_usd),
+        models = if models.is_empty() {
+            "-".to_string()
+        } else {
+            models.join(" ")
+        },
+        branch = escape_html(session.git_branch.as_deref().unwrap_or("-")),
+        root = session.root,
+        note = note,
+        tools = render_tool_table(&detail.tools),
+        timeline = timeline,
+    );
+
+    render_page(
+        &format!(
+            "Session {} - Claude Monitor",
+            escape_html(&session.session_id)
+        ),
+        "",
+        &content,
+    )
+}
+
+/// Render the page of one project with its session history
+pub fn render_project_page(detail: &ProjectDetail, show_roots: bool) -> String {
+    let days = &detail.daily.buckets;
+    let max = days.iter().map(|d| d.usage.total()).max().unwrap_or(0).max(1);
+    let bars: Vec<String> = days
+        .iter()
+        .map(|day| {
+            let tokens = |group: &str| day.groups.get(group).map(|u| u.total()).unwrap_or(0);
+            let (main, agent) = (tokens("main"), tokens("agent"));
+            format!(
+                r#"<div class="chart-bar" title="{date}: {total} tokens, {cost}">
+                <div class="chart-segment agent" style="height: {agent_height:.1}%;"></div>
+                <div class="chart-segment" style="height: {main_height:.1}%;"></div>
+            </div>"#,
+                date = day.start.with_timezone(&Local).format("%b %d"),
+                total = format_tokens(main + agent),
+                cost = format_cost(day.cost_usd),
+                agent_height = agent as f64 / max as f64 * 100.0,
+                main_height = main as f64 / max as f64 * 100.0,
+            )
+        })
+        .collect();
+    let axis_label = |day: Option<&TimeBucket>| {
+        day.map(|d| d.start.with_timezone(&Local).format("%b %d").to_string())
+            .unwrap_or_default()
+    };
+
+    let sessions: Vec<&SessionData> = detail.sessions.iter().collect();
+    let avg_tokens = |stats: &SessionTypeStats| {
+        if stats.session_count > 0 {
+            format_tokens(stats.usage.total() / stats.session_count as u64)
+        } else {
+            "-".to_string()
+        }
+    };
+
+    let content = format!(
+        r#"    <div class="container">
+        <div class="header">
+            <h1>{path}</h1>
+            <div class="header-actions">
+                <a class="back-link" href="/history?project={history_project}">Prompts</a>
+                <a class="back-link" href="/">&larr; Dashboard</a>
+            </div>
+        </div>
+
+        <div class="stats-grid">
+            <div class="stat-card">
+                <div class="stat-label">Total Tokens</div>
+                <div class="stat-value highlight">{tokens}</div>
+            </div>
+            <div class="stat-card">
+                <div class="stat-label">Cost</div>
+                <div class="stat-value green">{cost}</div>
+            </div>
+            <div class="stat-card">
+                <div class="stat-label">Sessions</div>
+                <div class="stat-value">{session_count}</div>
+            </div>
+            <div class="stat-card">
+                <div class="stat-label">Avg Tokens / Session</div>
+                <div class="stat-value">{avg_tokens}</div>
+            </div>
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Main Sessions vs Agents</h2>
+            <ul class="model-list">
+                <li class="model-item">
+                    <div class="model-info"><span class="badge">Session</span></div>
+                    <div class="model-stats">
+                        <span>{main_count} sessions</span>
+                        <span>{main_messages} msgs</span>
+                        <span>{main_tokens} tokens</span>
+                        <span>{main_avg} avg</span>
+                        <span>{main_cost}</span>
+                    </div>
+                </li>
+                <li class="model-item">
+                    <div class="model-info"><span class="badge agent">Agent</span></div>
+                    <div class="model-stats">
+                        <span>{agent_count} agents</span>
+                        <span>{agent_messages} msgs</span>
+                        <span>{agent_tokens} tokens</span>
+                        <span>{agent_avg} avg</span>
+                        <span>{agent_cost}</span>
+                    </div>
+                </li>
+            </ul>
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Branches</h2>
+            {branches}
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Tools</h2>
+            {tools}
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Daily Usage</h2>
+            <div class="chart">
+                {bars}
+            </div>
+            <div class="chart-axis"><span>{first_day}</span><span>{last_day}</span></div>
+            <div class="chart-legend"><span>Main</span><span class="agent">Agent</span></div>
+        </div>
+
+        <div class="section">
+            <h2 class="section-title">Sessions ({session_count})</h2>
+            <ul class="session-list">
+                {sessions}
+            </ul>
+        </div>
+    </div>"#,
+        path = escape_html(&detail.path),
+        history_project = path_segment(&detail.path),
+        tokens = format_tokens(detail.usage.total()),
+        cost = format_cost(detail.cost_usd),
+        session_count = detail.sessions.len(),
+        avg_tokens = format_tokens(detail.avg_tokens_per_session.round() as u64),
+        main_count = detail.main.session_count,
+        main_messages = detail.main.message_count,
+        main_tokens = format_tokens(detail.main.usage.total()),
+        main_avg = avg_tokens(&detail.main),
+        main_cost = format_cost(detail.main.cost_usd),
+        agent_count = detail.agents.session_count,
+        agent_messages = detail.agents.message_count,
+        agent_tokens = format_tokens(detail.agents.usage.total()),
+        agent_avg = avg_tokens(&detail.agents),
+        agent_cost = format_cost(detail.agents.cost_usd),
+        bars = bars.join("\n"),
+        first_day = axis_label(days.first()),
+        last_day = axis_label(days.last()),
+        branches = render_branch_table(&detail.branches),
+        tools = render_tool_table(&detail.tools),
+        sessions = render_session_items(&sessions, show_roots),
+    );
+
+    render_page(
+        &format!("{} - Claude Monitor", escape_html(&detail.path)),
+        "",
+        &content,
+    )
+}
+
+/// Table of usage per git branch
+fn render_branch_table(branches: &[BranchStats]) -> String {
+    if branches.is_empty() {
+        return r#"<div class="empty">No usage recorded</div>"#.to_string();
+    }
+
+    let rows: Vec<String> = branches
+        .iter()
+        .map(|b| {
+            format!(
+                r#"<tr>
+                <td class="text">{branch}</td>
+                <td>{sessions}</td>
+                <td>{requests}</td>
+                <td>{tokens}</td>
+                <td>{cost}</td>
+                <td>{first_seen}</td>
+                <td>{last_seen}</td>
+            </tr>"#,
+                branch = escape_html(b.branch.as_deref().unwrap_or("(no branch)")),
+                sessions = b.session_count,
+                requests = b.request_count,
+                tokens = format_tokens(b.usage.total()),
+                cost = format_cost(b.cost_usd),
+                first_seen = b.first_seen.with_timezone(&Local).format("%b %d %H:%M"),
+                last_seen = b.last_seen.with_timezone(&Local).format("%b %d %H:%M"),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="data-table">
+            <tr>
+                <th class="text">Branch</th><th>Sessions</th><th>Requests</th><th>Tokens</th>
+                <th>Cost</th><th>First Seen</th><th>Last Seen</th>
+            </tr>
+            {rows}
+        </table>"#,
+        rows = rows.join("\n")
+    )
+}
+
+/// Render the dashboard's tool usage partial
+pub fn render_tools_partial(tools: &[ToolSummary]) -> String {
+    let shown = tools.len().min(DASHBOARD_TOOLS);
+    let title = if tools.len() > shown {
+        format!("Tools (top {} of {})", shown, tools.len())
+    } else {
+        "Tools".to_string()
+    };
+
+    format!(
+        r#"<div class="section">
+        <h2 class="section-title">{title}</h2>
+        {table}
+    </div>"#,
+        title = title,
+        table = render_tool_table(&tools[..shown]),
+    )
+}
+
+/// Table of tool calls, failures and the cost of the turns that made them
+fn render_tool_table(tools: &[ToolSummary]) -> String {
+    if tools.is_empty() {
+        return r#"<div class="empty">No tool calls</div>"#.to_string();
+    }
+
+    let rows: Vec<String> = tools
+        .iter()
+        .map(|t| {
+            format!(
+                r#"<tr>
+                <td class="text">{name}</td>
+                <td>{calls}</td>
+                <td>{errors}</td>
+                <td>{failure_rate:.1}%</td>
+                <td>{sessions}</td>
+                <td>{tokens}</td>
+                <td>{cost}</td>
+            </tr>"#,
+                name = escape_html(&t.name),
+                calls = t.calls,
+                errors = t.errors,
+                failure_rate = t.failure_rate * 100.0,
+                sessions = t.session_count,
+                tokens = format_tokens(t.usage.total()),
+                cost = format_cost(t.cost_usd),
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="data-table">
+            <tr>
+                <th class="text">Tool</th><th>Calls</th><th>Errors</th><th>Failure Rate</th>
+                <th>Sessions</th><th>Turn Tokens</th><th>Turn Cost</th>
+            </tr>
+            {rows}
+        </table>"#,
+        rows = rows.join("\n")
+    )
+}
+
+/// Render the prompt history browser, searching for `text` within `project` initially
+pub fn render_history_page(page: &HistoryPage, text: &str, project: Option<&str>) -> String {
+    let options: Vec<String> = page
+        .projects
+        .iter()
+        .map(|p| {
+            format!(
+                r#"<option value="{value}"{selected}>{value}</option>"#,
+                value = escape_html(p),
+                selected = if Some(p.as_str()) == project {
+                    " selected"
+                } else {
+                    ""
+                },
+            )
+        })
+        .collect();
+
+    let content = format!(
+        r##"    <div class="container">
+        <div class="header">
+            <h1>Prompt History</h1>
+            <a class="back-link" href="/">&larr; Dashboard</a>
+        </div>
+
+        <div class="section">
+            <form id="history-form" class="search-form" hx-get="/partials/history" hx-target="#history-results"
+                  hx-trigger="input changed delay:300ms, change, submit">
+                <input type="search" name="q" placeholder="Search prompts" value="{text}" autofocus>
+                <select name="project">
+                    <option value="">All projects</option>
+                    {options}
+                </select>
+                <input type="date" name="from" title="From">
+                <input type="date" name="to" title="To">
+            </form>
+            <div id="history-results">
+                {results}
+            </div>
+        </div>
+    </div>"##,
+        text = escape_html(text),
+        options = options.join("\n"),
+        results = render_history_partial(page),
+    );
+
+    render_page("Prompt History - Claude Monitor", "", &content)
+}
+
+/// Render prompt history search results with paging buttons
+pub fn render_history_partial(page: &HistoryPage) -> String {
+    if page.entries.is_empty() {
+        return r#"<div class="empty">No matching prompts</div>"#.to_string();
+    }
+
+    let items: Vec<String> = page
+        .entries
+        .iter()
+        .map(|e| {
+            let project = e
+                .project
+                .as_deref()
+                .map(|p| {
+                    format!(
+                        r#"<a href="{url}">{path}</a>"#,
+                        url = project_url(p),
+                        path = escape_html(p)
+                    )
+                })
+                .unwrap_or_default();
+            let session = match (&e.project, &e.session_id) {
+                (Some(project), Some(id)) if e.session_available => format!(
+                    r#"<a href="{url}">Session {id}</a>"#,
+                    url = session_link(&e.root, project, id),
+                    id = escape_html(&id[..8.min(id.len())]),
+                ),
+                _ => String::new(),
+            };
+
+            format!(
+                r#"<li class="prompt-item">
+                <div class="prompt-text">{prompt}</div>
+                <div class="prompt-meta">
+                    <span>{time}</span>
+                    {project}
+                    {session}
+                </div>
+            </li>"#,
+                prompt = escape_html(&e.prompt),
+                time = e
+                    .timestamp
+                    .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default(),
+                project = project,
+                session = session,
+            )
+        })
+        .collect();
+
+    let pager_button = |label: &str, offset: usize| {
+        format!(
+            r##"<button class="refresh-btn" hx-get="/partials/history?offset={offset}&limit={limit}"
+                hx-include="#history-form" hx-target="#history-results">{label}</button>"##,
+            offset = offset,
+            limit = page.limit,
+            label = label,
+        )
+    };
+    let previous = if page.offset > 0 {
+        pager_button("Newer", page.offset.saturating_sub(page.limit))
+    } else {
+        "<span></span>".to_string()
+    };
+    let next = if page.offset + page.entries.len() < page.total {
+        pager_button("Older", page.offset + page.entries.len())
+    } else {
+        "<span></span>".to_string()
+    };
+
+    format!(
+        r#"<ul class="prompt-list">{items}</ul>
+        <div class="pager">
+            {previous}
+            <span class="prompt-meta">{first}–{last} of {total}</span>
+            {next}
+        </div>"#,
+        items = items.join("\n"),
+        previous = previous,
+        first = page.offset + 1,
+        last = page.offset + page.entries.len(),
+        total = page.total,
+        next = next,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::timeseries::{Bucket, Timeseries};
+    use claude_monitor_core::stats::ProjectStats;
+    use std::path::Path;
+
+    const HOSTILE: &str = r#"/tmp/<img src=x onerror="alert(1)">&"#;
+
+    fn hostile_session() -> SessionData {
+        let mut session = SessionData::from_path(Path::new("/p/<b>&\".jsonl"), "default");
+        session.project_path = HOSTILE.to_string();
+        session
+    }
+
+    /// Fails if `html` contains markup that came from [`HOSTILE`]
+    fn assert_escaped(html: &str) {
+        assert!(!html.contains("<img"), "unescaped path in {}", html);
+        assert!(!html.contains("<b>"), "unescaped session id in {}", html);
+        assert!(html.contains("&lt;img src=x onerror=&quot;alert(1)&quot;&gt;&amp;"));
+    }
+
+    #[test]
+    fn session_pages_escape_paths_and_ids() {
+        let session = hostile_session();
+        assert_escaped(&render_sessions_partial(&[&session], false));
+
+        let detail = SessionDetail {
+            session,
+            messages: Vec::new(),
+            started_at: None,
+            ended_at: None,
+            duration_secs: None,
+            cache_hit_ratio: 0.0,
+            tools: Vec::new(),
+            from_index: false,
+        };
+        assert_escaped(&render_session_page(&detail));
+    }
+
+    #[test]
+    fn project_pages_escape_the_path() {
+        let daily = Timeseries::new(Bucket::Day, None, None, None);
+        let detail = ProjectDetail::new(
+            HOSTILE.to_string(),
+            vec![hostile_session()],
+            Vec::new(),
+            daily,
+        );
+        let html = render_project_page(&detail, false);
+        assert_escaped(&html);
+        assert!(html.contains("<title>/tmp/&lt;img"));
+    }
+
+    #[test]
+    fn dashboard_projects_escape_the_path() {
+        let stats = Stats {
+            projects: vec![ProjectStats::new(HOSTILE.to_string())],
+            ..Default::default()
+        };
+        let html = render_projects_list(&stats);
+        assert!(!html.contains("<img"), "unescaped path in {}", html);
+        assert!(html.contains("&lt;img src=x onerror=&quot;alert(1)&quot;&gt;&amp;"));
+    }
+}
@@ -1,9 +1,10 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod parser;
+mod stats;
 
-use parser::{get_stats, Stats};
+use claude_monitor_core::server;
+use stats::{get_stats, Stats};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tauri::{
@@ -17,6 +18,12 @@ struct AppState {
     stats: Arc<Mutex<Stats>>,
 }
 
+/// Seconds between re-parsing the transcripts when no server is running
+const LOCAL_REFRESH_SECS: u64 = 30;
+
+/// Seconds to wait before reconnecting after the server closed its update stream
+const RECONNECT_SECS: u64 = 5;
+
 fn format_tokens(count: u64) -> String {
     if count >= 1_000_000 {
         format!("{:.1}M", count as f64 / 1_000_000.0)
@@ -27,6 +34,14 @@ fn format_tokens(count: u64) -> String {
     }
 }
 
+fn format_minutes(minutes: i64) -> String {
+    if minutes >= 60 {
+        format!("{}h{:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
 fn make_progress_bar(percentage: f64, width: usize) -> String {
     let filled = ((percentage / 100.0) * width as f64).round() as usize;
     let empty = width.saturating_sub(filled);
@@ -67,6 +82,22 @@ fn build_menu(app: &tauri::AppHandle, stats: &Stats) -> Menu<tauri::Wry> {
     let remaining_item = MenuItem::new(app, &remaining_text, false, None::<&str>).unwrap();
     menu.append(&remaining_item).unwrap();
 
+    // Burn rate and projection
+    let burn = &stats.burn_rate;
+    let burn_text = match burn.minutes_to_exhaustion {
+        Some(minutes) => format!(
+            "   Burn: {}/min, runs out in {}",
+            format_tokens(burn.tokens_per_minute.round() as u64),
+            format_minutes(minutes)
+        ),
+        None => format!(
+            "   Burn: {}/min",
+            format_tokens(burn.tokens_per_minute.round() as u64)
+        ),
+    };
+    let burn_item = MenuItem::new(app, &burn_text, false, None::<&str>).unwrap();
+    menu.append(&burn_item).unwrap();
+
     // Separator
     let sep2 = MenuItem::new(app, "─────────────────────", false, None::<&str>).unwrap();
     menu.append(&sep2).unwrap();
@@ -146,8 +177,23 @@ fn build_menu(app: &tauri::AppHandle, stats: &Stats) -> Menu<tauri::Wry> {
 }
 
 fn build_title(stats: &Stats) -> String {
-    // Show budget percentage in title
-    format!("{:.0}%", stats.budget.percentage)
+    // Show budget percentage in title, plus time left if the budget runs out before the reset
+    match stats.burn_rate.minutes_to_exhaustion {
+        Some(minutes) => format!(
+            "{:.0}% ⚠{}",
+            stats.budget.percentage,
+            format_minutes(minutes)
+        ),
+        None => format!("{:.0}%", stats.budget.percentage),
+    }
+}
+
+fn update_tray(app: &tauri::AppHandle, stats: &Stats) {
+    if let Some(tray) = app.tray_by_id("main") {
+        let menu = build_menu(app, stats);
+        let _ = tray.set_menu(Some(menu));
+        let _ = tray.set_title(Some(&build_title(stats)));
+    }
 }
 
 fn main() {
@@ -187,7 +233,7 @@ fn main() {
                             app.exit(0);
                         }
                         "dashboard" => {
-                            let _ = app.shell().open("http://localhost:3456", None::<tauri_plugin_shell::open::Program>);
+                            let _ = app.shell().open(stats::dashboard_url(), None::<tauri_plugin_shell::open::Program>);
                         }
                         _ => {}
                     }
@@ -200,10 +246,7 @@ fn main() {
                     } = event
                     {
                         if let Ok(new_stats) = get_stats() {
-                            let app = tray.app_handle();
-                            let menu = build_menu(app, &new_stats);
-                            let _ = tray.set_menu(Some(menu));
-                            let _ = tray.set_title(Some(&build_title(&new_stats)));
+                            update_tray(tray.app_handle(), &new_stats);
                         }
                     }
                 })
@@ -212,16 +255,36 @@ fn main() {
             // Store state
             app.manage(AppState { stats: stats_arc.clone() });
 
-            // Auto-refresh every 30 seconds
+            // Follow the server's live updates while one is running,
+            // otherwise re-parse the transcripts periodically
             let app_handle = app.handle().clone();
             std::thread::spawn(move || {
                 loop {
-                    std::thread::sleep(Duration::from_secs(30));
-                    if let Ok(new_stats) = get_stats() {
-                        if let Some(tray) = app_handle.tray_by_id("main") {
-                            let menu = build_menu(&app_handle, &new_stats);
-                            let _ = tray.set_menu(Some(menu));
-                            let _ = tray.set_title(Some(&build_title(&new_stats)));
+                    match stats::server_port() {
+                        Some(port) => {
+                            let result = server::subscribe_stats(port, |new_stats| {
+                                update_tray(&app_handle, &new_stats);
+                            });
+                            // A server that can't be followed is only retried
+                            // after showing stats parsed from the transcripts
+                            if let Err(e) = result {
+                                eprintln!(
+                                    "Lost connection to server on port {}, parsing locally: {}",
+                                    port, e
+                                );
+                                if let Ok(new_stats) = stats::local_stats() {
+                                    update_tray(&app_handle, &new_stats);
+                                }
+                                std::thread::sleep(Duration::from_secs(LOCAL_REFRESH_SECS));
+                            } else {
+                                std::thread::sleep(Duration::from_secs(RECONNECT_SECS));
+                            }
+                        }
+                        None => {
+                            std::thread::sleep(Duration::from_secs(LOCAL_REFRESH_SECS));
+                            if let Ok(new_stats) = stats::local_stats() {
+                                update_tray(&app_handle, &new_stats);
+                            }
                         }
                     }
                 }
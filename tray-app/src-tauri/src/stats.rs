@@ -0,0 +1,66 @@
+use chrono::Utc;
+use claude_monitor_core::config::load_roots;
+use claude_monitor_core::parser::session::{parse_session_file, session_files};
+use claude_monitor_core::parser::{SessionData, TimestampedUsage};
+use claude_monitor_core::pricing::PricingTable;
+use claude_monitor_core::server::{self, PidFile};
+use claude_monitor_core::stats::{compute_stats, StatsSettings};
+
+pub use claude_monitor_core::stats::Stats;
+
+/// Port the dashboard is served on when no server is running
+const DEFAULT_PORT: u16 = 3456;
+
+/// Port of the running server, found through its PID file
+pub fn server_port() -> Option<u16> {
+    PidFile::read().map(|p| p.port)
+}
+
+/// URL of the web dashboard
+pub fn dashboard_url() -> String {
+    format!("http://localhost:{}", server_port().unwrap_or(DEFAULT_PORT))
+}
+
+/// Current stats from the running server, or parsed from the transcripts
+/// when no server is reachable
+pub fn get_stats() -> Result<Stats, Box<dyn std::error::Error>> {
+    if let Some(port) = server_port() {
+        match server::fetch_stats(port) {
+            Ok(stats) => return Ok(stats),
+            Err(e) => eprintln!(
+                "Server on port {} not responding, parsing locally: {}",
+                port, e
+            ),
+        }
+    }
+    local_stats()
+}
+
+/// Parse every transcript under the data roots the server would watch and
+/// aggregate them with the default budget settings
+pub fn local_stats() -> Result<Stats, Box<dyn std::error::Error>> {
+    let pricing = PricingTable::default();
+    let mut sessions: Vec<(SessionData, Vec<TimestampedUsage>)> = Vec::new();
+
+    for root in load_roots()? {
+        let projects_dir = root.projects_dir();
+        if !projects_dir.exists() {
+            continue;
+        }
+
+        for path in session_files(&projects_dir)? {
+            if let Ok((mut session, usages, _)) = parse_session_file(&path, &root.label) {
+                pricing.price_session(&mut session);
+                sessions.push((session, usages));
+            }
+        }
+    }
+
+    Ok(compute_stats(
+        sessions.iter().map(|(s, u)| (s, u.as_slice())),
+        Vec::new(),
+        &StatsSettings::default(),
+        &pricing,
+        Utc::now(),
+    ))
+}